@@ -75,20 +75,33 @@ impl TokenStreamPush for TokenStream {
     }
 }
 
-#[proc_macro_derive(SaveAndLoad)]
+/// Field attributes: #[save(skip)], #[save(map)], #[save(default)], #[save(default = value)], #[save(rename = "name")], and #[save(with = module)].
+/// Skipped fields are set to their default when loaded. Fields holding entities or handles must be mapped, which uses MapSerialisedEntities.
+#[proc_macro_derive(SaveAndLoad, attributes(save))]
 pub fn save_and_load(input: StdTokenStream) -> StdTokenStream {
     let input = parse_macro_input!(input as DeriveInput);
 
-    save_and_load::save_and_load(input)
+    save_and_load::save_and_load(input, save_and_load::Target::Component, true)
         .unwrap_or_else(syn::Error::into_compile_error)
         .into()
 }
 
+/// Takes the same field attributes as SaveAndLoad, and #[save(path = "path")] for the save path the resource belongs to.
+#[proc_macro_derive(SaveAndLoadResource, attributes(save))]
+pub fn save_and_load_resource(input: StdTokenStream) -> StdTokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    save_and_load::save_and_load(input, save_and_load::Target::Resource, true)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Like SaveAndLoad, but for a type from another crate. It isn't registered, so SavingPlugin does that.
 #[proc_macro]
 pub fn save_and_load_external(input: StdTokenStream) -> StdTokenStream {
     let input = parse_macro_input!(input as DeriveInput);
 
-    save_and_load::save_and_load(input)
+    save_and_load::save_and_load(input, save_and_load::Target::Component, false)
         .unwrap_or_else(syn::Error::into_compile_error)
         .into()
 }
@@ -1,6 +1,20 @@
+use syn::{ext::IdentExt, Fields, Member};
+
 use crate::prelude::*;
 
-pub fn save_and_load(input: DeriveInput) -> syn::Result<TokenStream> {
+/// What the derive is implementing saving and loading for.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    Component,
+    Resource,
+}
+
+/// register is false for types that are registered by hand, rather than through app!.
+pub fn save_and_load(
+    input: DeriveInput,
+    target: Target,
+    register: bool,
+) -> syn::Result<TokenStream> {
     let struct_ident = input.ident;
     let struct_ident_string_lowercase = struct_ident.to_string().to_lowercase();
     let serialised_struct_ident = Ident::new(
@@ -8,69 +22,534 @@ pub fn save_and_load(input: DeriveInput) -> syn::Result<TokenStream> {
         Span::call_site(),
     );
 
-    let file_extension = format!("{}.json", struct_ident_string_lowercase);
+    // Only overridden if #[save(version = N)] is present, so that the trait's default is used otherwise.
+    let mut version = None;
+    // Resources have no SaveConfig, so #[save(path = "...")] says which save path they belong to.
+    let mut save_path = None;
 
-    let Data::Struct(data) = input.data else {
-        todo!();
-    };
+    input.attrs.iter().try_for_each(|attribute| {
+        if !attribute.path().is_ident("save") {
+            return Ok(());
+        }
+
+        attribute.parse_nested_meta(|meta| match target {
+            Target::Component if meta.path.is_ident("version") => {
+                let value: syn::LitInt = meta.value()?.parse()?;
+                version = Some(quote! {const VERSION: u32 = #value;});
+                Ok(())
+            }
+            Target::Resource if meta.path.is_ident("path") => {
+                let value: syn::LitStr = meta.value()?.parse()?;
+                save_path = Some(value);
+                Ok(())
+            }
+            Target::Component => Err(meta.error("Expected version = N.")),
+            Target::Resource => Err(meta.error("Expected path = \"path\".")),
+        })
+    })?;
+
+    if target == Target::Resource && save_path.is_none() {
+        return Err(syn::Error::new_spanned(
+            &struct_ident,
+            "SaveAndLoadResource needs #[save(path = \"path\")], the save path to save the \
+             resource in.",
+        ));
+    }
 
-    let mut serialised_fields = vec![];
-    let mut entity_serialisations = vec![];
-    let mut entity_deserialisations = vec![];
+    let generics = input.generics;
+    let (impl_generics, type_generics, where_clause) = generics.split_for_impl();
+    let is_empty;
+
+    // The serialised type must have the same shape as the original, so structs stay structs, and enums stay enums.
+    let serialised_definition;
     let mut to_serialised = vec![];
     let mut from_serialised = vec![];
+    let mut default_functions = vec![];
 
-    data.fields.into_iter().for_each(|field| {
-        let field_ident = field.ident.unwrap();
-        let field_type = field.ty;
+    match input.data {
+        Data::Struct(data) => {
+            is_empty = false;
 
-        let field_type_as_string = field_type.to_token_stream().to_string();
+            let mut shape = Shape::new(data.fields, &serialised_struct_ident, "")?;
+            default_functions.extend(shape.default_functions.iter().cloned());
+            let unused = unused_type_parameters(&generics, &shape.serialised_types);
+            if !unused.is_empty() {
+                shape.add_phantom(&unused);
+            }
 
-        if field_type_as_string == "Entity" {
-            serialised_fields.push(quote! {#field_ident: crate::saving::SerialisedEntity,});
-            entity_deserialisations.push(quote!{let #field_ident = deserialise_entity.convert(serialised.#field_ident, commands);});
-            entity_serialisations
-                .push(quote! {let #field_ident = serialise_entity.convert(self.#field_ident);});
-            to_serialised.push(quote! {#field_ident,});
-            from_serialised.push(quote!{#field_ident,});
-        } else {
-            serialised_fields.push(quote! {#field_ident: #field_type,});
-            to_serialised.push(quote! {#field_ident: self.#field_ident.clone(),});
-            from_serialised.push(quote! {#field_ident: serialised.#field_ident.clone(),});
+            serialised_definition = match &shape.fields {
+                Fields::Named(_) => {
+                    let fields = &shape.serialised_fields;
+                    quote! {
+                        pub struct #serialised_struct_ident #generics #where_clause {
+                            #(#fields)*
+                        }
+                    }
+                }
+                Fields::Unnamed(_) => {
+                    let fields = &shape.serialised_fields;
+                    quote! {
+                        pub struct #serialised_struct_ident #generics (#(#fields)*) #where_clause;
+                    }
+                }
+                Fields::Unit => quote! {
+                    pub struct #serialised_struct_ident #generics #where_clause;
+                },
+            };
+
+            to_serialised
+                .push(shape.serialise_arm(quote! {Self}, quote! {#serialised_struct_ident}));
+            from_serialised
+                .push(shape.deserialise_arm(quote! {#serialised_struct_ident}, quote! {Self}));
+        }
+        Data::Enum(data) => {
+            is_empty = data.variants.is_empty();
+            let mut variants = vec![];
+            let mut serialised_types = vec![];
+
+            for variant in data.variants {
+                let variant_ident = variant.ident;
+                let shape = Shape::new(
+                    variant.fields,
+                    &serialised_struct_ident,
+                    &format!("{}_", variant_ident.unraw().to_string().to_lowercase()),
+                )?;
+                default_functions.extend(shape.default_functions.iter().cloned());
+                serialised_types.extend(shape.serialised_types.iter().cloned());
+                let fields = &shape.serialised_fields;
+
+                variants.push(match &shape.fields {
+                    Fields::Named(_) => quote! {#variant_ident {#(#fields)*},},
+                    Fields::Unnamed(_) => quote! {#variant_ident (#(#fields)*),},
+                    Fields::Unit => quote! {#variant_ident,},
+                });
+
+                to_serialised.push(shape.serialise_arm(
+                    quote! {Self::#variant_ident},
+                    quote! {#serialised_struct_ident::#variant_ident},
+                ));
+                from_serialised.push(shape.deserialise_arm(
+                    quote! {#serialised_struct_ident::#variant_ident},
+                    quote! {Self::#variant_ident},
+                ));
+            }
+
+            // The variant is never made, it only uses the type parameters that only skipped fields have.
+            let unused = unused_type_parameters(&generics, &serialised_types);
+            if !unused.is_empty() {
+                variants.push(quote! {
+                    #[serde(skip)]
+                    #[doc(hidden)]
+                    __Phantom(core::marker::PhantomData<(#(#unused,)*)>),
+                });
+                from_serialised.push(quote! {
+                    #serialised_struct_ident::__Phantom(_) => unreachable!(),
+                });
+            }
+
+            serialised_definition = quote! {
+                pub enum #serialised_struct_ident #generics #where_clause {
+                    #(#variants)*
+                }
+            };
+        }
+        Data::Union(data) => {
+            return Err(syn::Error::new(
+                data.union_token.span,
+                "SaveAndLoad can't be derived for unions.",
+            ));
         }
+    }
+
+    // Every field is cloned, so every type parameter must be Clone.
+    let mut impl_where_clause = where_clause.cloned().unwrap_or_else(|| syn::WhereClause {
+        where_token: Default::default(),
+        predicates: Default::default(),
+    });
+    generics.type_params().for_each(|type_parameter| {
+        let type_parameter = &type_parameter.ident;
+        impl_where_clause
+            .predicates
+            .push(syn::parse_quote! {#type_parameter: Clone});
+    });
+    impl_where_clause.predicates.push(match target {
+        Target::Component => syn::parse_quote! {#struct_ident #type_generics: Component},
+        Target::Resource => syn::parse_quote! {#struct_ident #type_generics: Resource},
+    });
+    impl_where_clause.predicates.push(syn::parse_quote! {
+        #serialised_struct_ident #type_generics: Serialize + serde::de::DeserializeOwned
     });
 
-    Ok(quote! {
-        #[derive(Asset, TypePath, Serialize, Deserialize)]
-        pub struct #serialised_struct_ident {
-            #(#serialised_fields)*
+    // An enum without variants can't be matched on through a reference.
+    let (serialise_match, deserialise_match) = if is_empty {
+        (quote! {*self}, quote! {*serialised})
+    } else {
+        (quote! {self}, quote! {serialised})
+    };
+
+    // Components may be put on their entity some other way, so they return an Option. Derived ones never are.
+    let (trait_path, setup, constants, deserialised_type, deserialised) = match target {
+        Target::Component => (
+            quote! {crate::saving::SaveAndLoad},
+            quote! {crate::saving::setup_app_for_saving_and_loading},
+            quote! {#version},
+            quote! {Option<Self>},
+            quote! {Some(match #deserialise_match {
+                #(#from_serialised)*
+            })},
+        ),
+        Target::Resource => (
+            quote! {crate::saving::SaveAndLoadResource},
+            quote! {crate::saving::setup_app_for_saving_and_loading_resource},
+            quote! {const PATH: &str = #save_path;},
+            quote! {Self},
+            quote! {match #deserialise_match {
+                #(#from_serialised)*
+            }},
+        ),
+    };
+
+    // A generic type has no single type to register, so each use of it must be registered by hand.
+    let registration = if register && generics.params.is_empty() {
+        quote! {
+            app!(|app| {
+                #setup::<#struct_ident>(app);
+            });
         }
+    } else {
+        quote! {}
+    };
 
-        impl crate::saving::SaveAndLoad for #struct_ident {
-            type Serialised = #serialised_struct_ident;
+    let default_functions = if default_functions.is_empty() {
+        quote! {}
+    } else {
+        quote! {
+            impl #impl_generics #serialised_struct_ident #type_generics #where_clause {
+                #(#default_functions)*
+            }
+        }
+    };
 
-            fn serialise(&self, serialise_entity: &mut crate::saving::SerialiseEntity) -> Self::Serialised {
-                #(#entity_serialisations)*
+    Ok(quote! {
+        #[derive(Serialize, Deserialize)]
+        #serialised_definition
+
+        #default_functions
 
-                Self::Serialised {
+        impl #impl_generics #trait_path for #struct_ident #type_generics #impl_where_clause {
+            type Serialised = #serialised_struct_ident #type_generics;
+
+            fn serialise(&self, serialise_entity: &mut crate::saving::SerialiseEntity) -> Self::Serialised {
+                match #serialise_match {
                     #(#to_serialised)*
                 }
             }
 
-            fn deserialise(serialised: &Self::Serialised, deserialise_entity: &mut crate::saving::DeserialiseEntity, commands: &mut Commands) -> Self {
-                #(#entity_deserialisations)*
+            fn deserialise(serialised: &Self::Serialised, deserialise_entity: &mut crate::saving::DeserialiseEntity, commands: &mut Commands) -> #deserialised_type {
+                #deserialised
+            }
 
-                Self {
-                    #(#from_serialised)*
+            const STRUCT_IDENT_LOWERCASE: &str = #struct_ident_string_lowercase;
+            #constants
+        }
+
+        #registration
+    })
+}
+
+/// The fields of a struct or an enum variant.
+struct Shape {
+    fields: Fields,
+    serialised_fields: Vec<TokenStream>,
+    // The types of the fields that are serialised, to find the type parameters only skipped fields use.
+    serialised_types: Vec<TokenStream>,
+    // A PhantomData field for those type parameters, as the serialised type must still use them.
+    phantom: Option<Member>,
+    bindings: Vec<Binding>,
+    // Functions for #[serde(default = "...")], which only takes a path.
+    default_functions: Vec<TokenStream>,
+}
+
+struct Binding {
+    member: Member,
+    // None if the field is skipped. Skipped fields shift the indices of later tuple fields.
+    serialised_member: Option<Member>,
+    // Bindings are prefixed, so they can't shadow the parameters of serialise and deserialise.
+    ident: Ident,
+    // Fields marked #[save(map)] are mapped with MapSerialisedEntities instead of cloned.
+    mapped: bool,
+    default: TokenStream,
+}
+
+impl Shape {
+    /// The prefix keeps default functions of different variants apart.
+    fn new(fields: Fields, serialised_struct_ident: &Ident, prefix: &str) -> syn::Result<Self> {
+        let mut serialised_fields = vec![];
+        let mut serialised_types = vec![];
+        let mut bindings = vec![];
+        let mut default_functions = vec![];
+
+        for (index, field) in fields.iter().enumerate() {
+            let field_type = &field.ty;
+
+            let mut skip = false;
+            let mut mapped = false;
+            let mut default = None;
+            let mut rename = None;
+            let mut with = None;
+
+            for attribute in &field.attrs {
+                if !attribute.path().is_ident("save") {
+                    continue;
                 }
+
+                attribute.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("skip") {
+                        skip = true;
+                    } else if meta.path.is_ident("map") {
+                        mapped = true;
+                    } else if meta.path.is_ident("default") {
+                        default = Some(if meta.input.peek(syn::Token![=]) {
+                            Some(meta.value()?.parse::<syn::Expr>()?)
+                        } else {
+                            None
+                        });
+                    } else if meta.path.is_ident("rename") {
+                        if field.ident.is_none() {
+                            return Err(meta.error("Only named fields can be renamed."));
+                        }
+                        let value = meta.value()?.parse::<syn::LitStr>()?;
+                        // Reserved for the version that every component is written with.
+                        if value.value().starts_with('$') {
+                            return Err(syn::Error::new_spanned(
+                                value,
+                                "Names starting with $ are reserved.",
+                            ));
+                        }
+                        rename = Some(value);
+                    } else if meta.path.is_ident("with") {
+                        with = Some(meta.value()?.parse::<syn::Path>()?);
+                    } else {
+                        return Err(meta.error(
+                            "Expected skip, map, default, default = value, rename = \"name\", or \
+                             with = module.",
+                        ));
+                    }
+                    Ok(())
+                })?;
             }
 
-            const STRUCT_IDENT_LOWERCASE: &str = #struct_ident_string_lowercase;
-            const FILE_EXTENSION: &str = #file_extension;
+            let name = match &field.ident {
+                Some(field_ident) => field_ident.unraw().to_string(),
+                None => index.to_string(),
+            };
+            let member = match &field.ident {
+                Some(field_ident) => Member::Named(field_ident.clone()),
+                None => Member::Unnamed(index.into()),
+            };
+            let ident = Ident::new(&format!("field_{name}"), Span::call_site());
+
+            let default_value = match &default {
+                Some(Some(value)) => quote! {#value},
+                _ => quote! {Default::default()},
+            };
+
+            if skip {
+                if rename.is_some() || with.is_some() || mapped {
+                    return Err(syn::Error::new_spanned(
+                        field,
+                        "A skipped field can't be renamed, mapped, or use with.",
+                    ));
+                }
+
+                bindings.push(Binding {
+                    member,
+                    serialised_member: None,
+                    ident,
+                    mapped,
+                    default: default_value,
+                });
+                continue;
+            }
+
+            if mapped && with.is_some() {
+                return Err(syn::Error::new_spanned(
+                    field,
+                    "A mapped field can't use with, as its entities would never be mapped.",
+                ));
+            }
+            // Entities can't be saved as they are. This only catches the obvious cases, so your own types holding entities must be marked too.
+            if !mapped && mentions(field_type.to_token_stream(), &["Entity", "Handle"]) {
+                return Err(syn::Error::new_spanned(
+                    field,
+                    "Fields holding entities or handles must be marked #[save(map)], or skipped.",
+                ));
+            }
+
+            let serialised_type = if mapped {
+                quote! {<#field_type as crate::saving::MapSerialisedEntities>::Serialised}
+            } else {
+                quote! {#field_type}
+            };
+
+            let mut serde_attributes = vec![];
+            match default {
+                Some(Some(_)) if mapped => {
+                    return Err(syn::Error::new_spanned(
+                        field,
+                        "Mapped fields can only have a default value if they are skipped.",
+                    ));
+                }
+                Some(Some(_)) => {
+                    let function_ident =
+                        Ident::new(&format!("default_{prefix}{name}"), Span::call_site());
+                    let path = format!("{serialised_struct_ident}::{function_ident}");
+                    default_functions.push(quote! {
+                        fn #function_ident() -> #serialised_type {
+                            #default_value
+                        }
+                    });
+                    serde_attributes.push(quote! {#[serde(default = #path)]});
+                }
+                Some(None) => serde_attributes.push(quote! {#[serde(default)]}),
+                None => (),
+            }
+            if let Some(rename) = rename {
+                serde_attributes.push(quote! {#[serde(rename = #rename)]});
+            }
+            if let Some(with) = with {
+                let with = with.to_token_stream().to_string();
+                serde_attributes.push(quote! {#[serde(with = #with)]});
+            }
+
+            serialised_types.push(serialised_type.clone());
+            let serialised_member = if let Some(field_ident) = &field.ident {
+                serialised_fields
+                    .push(quote! {#(#serde_attributes)* #field_ident: #serialised_type,});
+                Member::Named(field_ident.clone())
+            } else {
+                let serialised_index = serialised_fields.len();
+                serialised_fields.push(quote! {#(#serde_attributes)* #serialised_type,});
+                Member::Unnamed(serialised_index.into())
+            };
+
+            bindings.push(Binding {
+                member,
+                serialised_member: Some(serialised_member),
+                ident,
+                mapped,
+                default: default_value,
+            });
+        }
+
+        Ok(Self {
+            fields,
+            serialised_fields,
+            serialised_types,
+            phantom: None,
+            bindings,
+            default_functions,
+        })
+    }
+
+    fn add_phantom(&mut self, unused: &[&Ident]) {
+        let phantom_type = quote! {core::marker::PhantomData<(#(#unused,)*)>};
+        let member = if let Fields::Named(_) = self.fields {
+            let field_ident = Ident::new("__phantom", Span::call_site());
+            self.serialised_fields
+                .push(quote! {#[serde(skip)] #field_ident: #phantom_type,});
+            Member::Named(field_ident)
+        } else {
+            let serialised_index = self.serialised_fields.len();
+            self.serialised_fields
+                .push(quote! {#[serde(skip)] #phantom_type,});
+            Member::Unnamed(serialised_index.into())
+        };
+        self.phantom = Some(member);
+    }
+
+    /// Braced patterns and constructors work for every kind of struct and variant, so they are always used.
+    fn pattern<'a>(
+        &'a self,
+        path: &TokenStream,
+        member: impl Fn(&'a Binding) -> Option<&'a Member>,
+    ) -> TokenStream {
+        let members = self
+            .bindings
+            .iter()
+            .filter_map(|binding| {
+                let ident = &binding.ident;
+                member(binding).map(|member| quote! {#member: #ident})
+            })
+            .collect::<Vec<_>>();
+
+        if members.len() == self.bindings.len() && self.phantom.is_none() {
+            quote! {#path {#(#members),*}}
+        } else {
+            quote! {#path {#(#members,)* ..}}
         }
+    }
 
-        app!(|app| {
-            crate::saving::setup_app_for_saving_and_loading::<#struct_ident>(app);
+    fn serialise_arm(&self, from: TokenStream, to: TokenStream) -> TokenStream {
+        let pattern = self.pattern(&from, |binding| {
+            binding.serialised_member.as_ref().map(|_| &binding.member)
         });
+        let members = self.bindings.iter().filter_map(|binding| {
+            let member = binding.serialised_member.as_ref()?;
+            let ident = &binding.ident;
+            Some(if binding.mapped {
+                quote! {#member: crate::saving::MapSerialisedEntities::serialise(#ident, serialise_entity)}
+            } else {
+                quote! {#member: #ident.clone()}
+            })
+        });
+        let phantom = self
+            .phantom
+            .iter()
+            .map(|member| quote! {#member: core::marker::PhantomData});
+        quote! {#pattern => #to {#(#members,)* #(#phantom)*},}
+    }
+
+    fn deserialise_arm(&self, from: TokenStream, to: TokenStream) -> TokenStream {
+        let pattern = self.pattern(&from, |binding| binding.serialised_member.as_ref());
+        let members = self.bindings.iter().map(|binding| {
+            let member = &binding.member;
+            let ident = &binding.ident;
+            if binding.serialised_member.is_none() {
+                let default = &binding.default;
+                quote! {#member: #default}
+            } else if binding.mapped {
+                quote! {#member: crate::saving::MapSerialisedEntities::deserialise(#ident, deserialise_entity, commands)}
+            } else {
+                quote! {#member: #ident.clone()}
+            }
+        });
+        quote! {#pattern => #to {#(#members),*},}
+    }
+}
+
+/// Whether any of the idents appear anywhere in the tokens, such as Entity in Option<Entity> or Vec<(f32, Entity)>.
+fn mentions(tokens: TokenStream, idents: &[&str]) -> bool {
+    tokens.into_iter().any(|token_tree| match token_tree {
+        TokenTree::Ident(ident) => idents.iter().any(|other| ident == other),
+        TokenTree::Group(group) => mentions(group.stream(), idents),
+        _ => false,
     })
 }
+
+/// The type parameters that no serialised field uses, because only skipped fields have them.
+fn unused_type_parameters<'a>(
+    generics: &'a syn::Generics,
+    serialised_types: &[TokenStream],
+) -> Vec<&'a Ident> {
+    generics
+        .type_params()
+        .map(|type_parameter| &type_parameter.ident)
+        .filter(|type_parameter| {
+            let type_parameter = type_parameter.to_string();
+            !serialised_types
+                .iter()
+                .any(|tokens| mentions(tokens.clone(), &[&type_parameter]))
+        })
+        .collect()
+}
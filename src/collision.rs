@@ -1,104 +1,123 @@
-use std::array;
-
 use bevy::{ecs::query::QueryFilter, utils::Parallel};
 
 pub use crate::prelude::*;
 
-pub mod prelude {
-    pub type ColliderGrid = super::ColliderGrid<GRID_WIDTH, GRID_HEIGHT>;
+mod cast;
+mod collider;
+mod layers;
+mod sensor;
 
+pub mod prelude {
+    pub use super::{
+        cast::prelude::*, collider::prelude::*, layers::prelude::*, sensor::prelude::*,
+    };
     pub use super::{
-        check_collision, collide, distance_between_edges,
+        check_collision, collide, distance_between_edges, ColliderGrid, ColliderQuery,
         CollisionQuestion as CollisionQuestionDeprecated,
-        CollisionSensor as CollisionSensorDeprecated,
         DistanceSquaredBetweenEdgesQuestion as DistanceSquaredBetweenEdgesQuestionDeprecated,
-        Radius, GRID_CELL_SIZE, GRID_HEIGHT, GRID_ORIGIN, GRID_WIDTH,
+        Radius, GRID_CELL_SIZE,
     };
 }
 
-pub const GRID_WIDTH: usize = 120;
-pub const GRID_HEIGHT: usize = 25;
+/// The cell size ColliderGrid starts with.
 pub const GRID_CELL_SIZE: Vec2 = Vec2::new(100., 100.);
-pub const GRID_ORIGIN: Vec2 = Vec2::new(-1000., 0.);
 
 //#[system(Update)]
-fn grid_bounds_debug(mut gizmos: Gizmos, mut menu: MenuReader) {
+fn grid_cells_debug(mut gizmos: Gizmos, mut menu: MenuReader, collider_grid: Res<ColliderGrid>) {
     if !menu.is(Menu::InGame) {
         return;
     }
 
-    let size = Vec2::new(
-        GRID_WIDTH as f32 * GRID_CELL_SIZE.x,
-        GRID_HEIGHT as f32 * GRID_CELL_SIZE.y,
-    );
-    gizmos.rect_2d(GRID_ORIGIN + size * 0.5, size, Color::srgb(1., 1., 0.));
+    collider_grid.cells.keys().for_each(|cell| {
+        gizmos.rect_2d(
+            (cell.as_vec2() + 0.5) * collider_grid.cell_size,
+            collider_grid.cell_size,
+            Color::srgb(1., 1., 0.),
+        );
+    });
 }
 
+/// What grid queries need from every collider. Colliders without CollisionLayers use the default ones.
+pub type ColliderQuery<'w, 's, T = ()> = Query<
+    'w,
+    's,
+    (
+        &'static Collider,
+        &'static Transform,
+        Option<&'static CollisionLayers>,
+    ),
+    T,
+>;
+
+/// A sparse spatial hash of every entity with a Collider, so it has no bounds.
+/// Cells are only stored while something is in or next to them.
 #[derive(Resource)]
-pub struct ColliderGrid<const WIDTH: usize, const HEIGHT: usize>
-where
-    [(); WIDTH * HEIGHT]:,
-{
-    // Bottom left.
-    pub origin: Vec2,
+pub struct ColliderGrid {
+    /// Can be changed at any time, but queries use the old cells until the next update.
+    /// Colliders are added to every cell they cover, so only what is queried needs to be smaller than a cell.
+    pub cell_size: Vec2,
 
     // Experiments to try, should performance become unreasonable.
     // Store x and check for x overlap before fetching the real translation.
     // Store the whole translation.
-    pub cells: Box<[(Vec<Entity>, Parallel<Vec<Entity>>); WIDTH * HEIGHT]>,
+    cells: HashMap<IVec2, Vec<Entity>>,
+    /// The smallest rectangle of cells containing every stored cell, so casts know when to give up.
+    occupied: Option<IRect>,
+    // Filled in parallel during update, then moved into the cells.
+    insertions: Parallel<Vec<(IVec2, Entity)>>,
 }
 
-impl<const WIDTH: usize, const HEIGHT: usize> Default for ColliderGrid<WIDTH, HEIGHT>
-where
-    [(); WIDTH * HEIGHT]:,
-{
+impl Default for ColliderGrid {
     fn default() -> Self {
-        Self {
-            origin: GRID_ORIGIN,
-            cells: Box::new(array::from_fn(|_| (vec![], default()))),
-        }
+        Self::new(GRID_CELL_SIZE)
     }
 }
 
-impl<const WIDTH: usize, const HEIGHT: usize> ColliderGrid<WIDTH, HEIGHT>
-where
-    [(); WIDTH * HEIGHT]:,
-{
-    pub fn new(origin: Vec2) -> Self {
+impl ColliderGrid {
+    pub fn new(cell_size: Vec2) -> Self {
         Self {
-            origin,
-            cells: Box::new(array::from_fn(|_| (vec![], default()))),
+            cell_size,
+            cells: HashMap::default(),
+            occupied: None,
+            insertions: default(),
         }
     }
 
+    /// Every entity in or next to the cell.
+    pub fn cell(&self, cell: IVec2) -> &[Entity] {
+        self.cells.get(&cell).map_or(&[], Vec::as_slice)
+    }
+
     pub fn get_collisions<T: QueryFilter>(
         &self,
         translation: Vec2,
         radius: f32,
         ignore: Option<Entity>,
-        colliders: &Query<(&Radius, &Transform), T>,
+        layers: CollisionLayers,
+        colliders: &ColliderQuery<T>,
     ) -> Collisions {
         let mut collisions = Collisions::default();
 
-        let Some(grid_index) = self.translation_to_index(translation) else {
-            return collisions;
-        };
+        let cell = self.translation_to_cell(translation);
 
-        self.cells[grid_index].0.iter().for_each(|other_entity| {
+        self.cell(cell).iter().for_each(|other_entity| {
             if let Some(ignore) = ignore {
                 if ignore == *other_entity {
                     return;
                 }
             }
 
-            let (other_collider, other_transform) = colliders.get(*other_entity).unwrap();
+            let Ok((other_collider, other_transform, other_layers)) = colliders.get(*other_entity)
+            else {
+                return;
+            };
 
-            if check_collision(
-                radius,
-                translation,
-                other_collider.0,
-                other_transform.translation.xy(),
-            ) {
+            if !layers.interacts(&other_layers.copied().unwrap_or_default()) {
+                return;
+            }
+
+            if other_collider.overlaps_circle(other_transform.translation.xy(), radius, translation)
+            {
                 collisions.add(*other_entity);
             }
         });
@@ -110,33 +129,28 @@ where
         translation: Vec2,
         radius: f32,
         ignore: Option<Entity>,
-        colliders: &Query<(&Radius, &Transform), T>,
+        layers: CollisionLayers,
+        colliders: &ColliderQuery<T>,
     ) -> bool {
-        let Some(grid_index) = self.translation_to_index(translation) else {
-            return false;
-        };
+        let cell = self.translation_to_cell(translation);
 
-        self.cells[grid_index].0.iter().any(|other_entity| {
+        self.cell(cell).iter().any(|other_entity| {
             if let Some(ignore) = ignore {
                 if ignore == *other_entity {
                     return false;
                 }
             }
 
-            let Ok((other_collider, other_transform)) = colliders.get(*other_entity) else {
+            let Ok((other_collider, other_transform, other_layers)) = colliders.get(*other_entity)
+            else {
                 return false;
             };
 
-            if check_collision(
-                radius,
-                translation,
-                other_collider.0,
-                other_transform.translation.xy(),
-            ) {
-                return true;
+            if !layers.interacts(&other_layers.copied().unwrap_or_default()) {
+                return false;
             }
 
-            false
+            other_collider.overlaps_circle(other_transform.translation.xy(), radius, translation)
         })
     }
 
@@ -146,7 +160,8 @@ where
         radius: f32,
         limit: f32,
         ignore: Option<Entity>,
-        colliders: &Query<(&Radius, &Transform), T>,
+        layers: CollisionLayers,
+        colliders: &ColliderQuery<T>,
         //TODO: Replace f32 with Option<f32>
     ) -> f32 {
         // With enough translation, it might leave the current grid cell. Keep that in mind!
@@ -165,6 +180,7 @@ where
                 translation + Vec2::new(0., y_translation),
                 radius,
                 ignore,
+                layers,
                 colliders,
             ) {
                 y_translation += radius * 2.;
@@ -172,35 +188,41 @@ where
                 y_translation -= radius * 2.;
                 let last_translation = translation + Vec2::new(0., y_translation);
 
-                let Some(grid_index) = self.translation_to_index(last_translation) else {
-                    todo!();
-                };
+                let cell = self.translation_to_cell(last_translation);
 
-                self.cells[grid_index].0.iter().for_each(|other_entity| {
+                self.cell(cell).iter().for_each(|other_entity| {
                     if let Some(ignore) = ignore {
                         if ignore == *other_entity {
                             return;
                         }
                     }
 
-                    let (other_collider, other_transform) = colliders.get(*other_entity).unwrap();
+                    let Ok((other_collider, other_transform, other_layers)) =
+                        colliders.get(*other_entity)
+                    else {
+                        return;
+                    };
+
+                    if !layers.interacts(&other_layers.copied().unwrap_or_default()) {
+                        return;
+                    }
+
+                    let other_top = other_collider
+                        .bounds(other_transform.translation.xy())
+                        .max
+                        .y;
 
-                    if other_transform.translation.y + other_collider.0
-                        <= translation.y + y_translation - radius
-                    {
+                    if other_top <= translation.y + y_translation - radius {
                         return;
                     }
 
-                    if check_collision(
+                    if other_collider.overlaps_circle(
+                        other_transform.translation.xy(),
                         radius,
                         last_translation,
-                        other_collider.0,
-                        other_transform.translation.xy(),
                     ) {
-                        // This is true only for jumping to the center of the circle. Probably good enough though.
-                        y_translation = other_transform.translation.y + other_collider.0
-                            - last_translation.y
-                            + radius;
+                        // This is true only for jumping to the center of the circle, or the top of anything else. Probably good enough though.
+                        y_translation = other_top - last_translation.y + radius;
                     }
                 });
                 break;
@@ -211,111 +233,58 @@ where
     }
 
     pub fn update(
-        mut collider_grid: ResMut<ColliderGrid<WIDTH, HEIGHT>>,
-        colliders: Query<(Entity, &Transform), With<Radius>>,
+        mut collider_grid: ResMut<ColliderGrid>,
+        colliders: Query<(Entity, &Transform, &Collider)>,
     ) {
-        colliders.par_iter().for_each(|(entity, transform)| {
-            // Do we want each entity to be in a neat box, or do we also want to push them into surrounding boxes, so that we only iterate one grid later?
-            // For now: One grid later.
-
-            let Some(index) = collider_grid.translation_to_index(transform.translation.xy()) else {
-                warn_once!("Collider out of bounds. {:?}", transform.translation.xy());
-                return;
-            };
-
-            // I don't know if any of this works. I'm just guessing. For now I'll go the slow route instead.
-
-            // if ! index + WIDTH >= WIDTH * HEIGHT {
-            //     collider_grid.cells[index + WIDTH].1.borrow_local_mut().push(entity);
-            // }
-
-            // if ! WIDTH > index {
-            //     collider_grid.cells[index - WIDTH].1.borrow_local_mut().push(entity);
-            // }
-
-            // if ! index + 1 >= WIDTH * HEIGHT {
-            //     collider_grid.cells[index + WIDTH].1.borrow_local_mut().push(entity);
-            // }
-
-            collider_grid.cells[index].1.borrow_local_mut().push(entity);
-
-            // Top
-
-            if let Some(index) = collider_grid.translation_to_index(
-                transform.translation.xy() + Vec2::new(-GRID_CELL_SIZE.x, GRID_CELL_SIZE.y),
-            ) {
-                collider_grid.cells[index].1.borrow_local_mut().push(entity);
-            }
-
-            if let Some(index) = collider_grid
-                .translation_to_index(transform.translation.xy() + Vec2::new(0., GRID_CELL_SIZE.y))
-            {
-                collider_grid.cells[index].1.borrow_local_mut().push(entity);
-            }
-
-            if let Some(index) = collider_grid.translation_to_index(
-                transform.translation.xy() + Vec2::new(GRID_CELL_SIZE.x, GRID_CELL_SIZE.y),
-            ) {
-                collider_grid.cells[index].1.borrow_local_mut().push(entity);
-            }
-
-            // Middle
-
-            if let Some(index) = collider_grid
-                .translation_to_index(transform.translation.xy() + Vec2::new(GRID_CELL_SIZE.x, 0.))
-            {
-                collider_grid.cells[index].1.borrow_local_mut().push(entity);
-            }
-
-            if let Some(index) = collider_grid
-                .translation_to_index(transform.translation.xy() + Vec2::new(-GRID_CELL_SIZE.x, 0.))
-            {
-                collider_grid.cells[index].1.borrow_local_mut().push(entity);
-            }
-
-            // Bottom
-
-            if let Some(index) = collider_grid.translation_to_index(
-                transform.translation.xy() + Vec2::new(-GRID_CELL_SIZE.x, -GRID_CELL_SIZE.y),
-            ) {
-                collider_grid.cells[index].1.borrow_local_mut().push(entity);
-            }
-
-            if let Some(index) = collider_grid
-                .translation_to_index(transform.translation.xy() + Vec2::new(0., -GRID_CELL_SIZE.y))
-            {
-                collider_grid.cells[index].1.borrow_local_mut().push(entity);
-            }
-
-            if let Some(index) = collider_grid.translation_to_index(
-                transform.translation.xy() + Vec2::new(GRID_CELL_SIZE.x, -GRID_CELL_SIZE.y),
-            ) {
-                collider_grid.cells[index].1.borrow_local_mut().push(entity);
-            }
+        let collider_grid = collider_grid.as_mut();
+
+        colliders
+            .par_iter()
+            .for_each(|(entity, transform, collider)| {
+                // Do we want each entity to be in a neat box, or do we also want to push them into surrounding boxes, so that we only iterate one grid later?
+                // For now: One grid later.
+                let bounds = collider.bounds(transform.translation.xy());
+                let min = collider_grid.translation_to_cell(bounds.min) - 1;
+                let max = collider_grid.translation_to_cell(bounds.max) + 1;
+                let mut insertions = collider_grid.insertions.borrow_local_mut();
+
+                for y in min.y..=max.y {
+                    for x in min.x..=max.x {
+                        insertions.push((IVec2::new(x, y), entity));
+                    }
+                }
+            });
+
+        // Cleared rather than removed, so that occupied cells keep their allocations.
+        collider_grid.cells.values_mut().for_each(Vec::clear);
+
+        let ColliderGrid {
+            cells,
+            occupied,
+            insertions,
+            ..
+        } = collider_grid;
+        insertions.iter_mut().for_each(|insertions| {
+            insertions
+                .drain(..)
+                .for_each(|(cell, entity)| cells.entry(cell).or_default().push(entity));
         });
 
-        collider_grid.cells.par_iter_mut().for_each(|cell| {
-            cell.0.clear();
-            cell.1.iter_mut().for_each(|vec| cell.0.append(vec));
+        // Anything left empty has nothing near it anymore.
+        cells.retain(|_, entities| !entities.is_empty());
+
+        *occupied = cells.keys().fold(None, |occupied: Option<IRect>, cell| {
+            Some(
+                occupied.map_or(IRect::from_corners(*cell, *cell), |occupied| {
+                    occupied.union_point(*cell)
+                }),
+            )
         });
     }
 
-    pub fn translation_to_index(&self, translation: Vec2) -> Option<usize> {
-        if translation.x < self.origin.x || translation.y < self.origin.y {
-            return None;
-        }
-
-        let corrected_translation = (translation - self.origin) / GRID_CELL_SIZE;
-        //info!("{}", corrected_translation);
-
-        if corrected_translation.x >= WIDTH as f32 || corrected_translation.y >= HEIGHT as f32 {
-            return None;
-        }
-
-        let float_index =
-            corrected_translation.y.trunc() * WIDTH as f32 + corrected_translation.x.trunc();
-        //info!(float_index);
-        Some(float_index.trunc() as usize)
+    /// The cell the translation is in. Cells are counted from the world origin, and go on forever in every direction.
+    pub fn translation_to_cell(&self, translation: Vec2) -> IVec2 {
+        (translation / self.cell_size).floor().as_ivec2()
     }
 }
 
@@ -411,23 +380,37 @@ impl DistanceSquaredBetweenEdgesQuestion {
     }
 }
 
-#[derive(Component, Default)]
-pub struct CollisionSensor {
-    pub collisions: Collisions,
-}
-
-impl CollisionSensor {
-    pub fn iter(&self) {
-        //self.collisions.
-        // collisions.iter_mut().flat_map(|collisions| {
-        //     collisions.iter()
-        // });
-    }
-}
-
+/// Shorthand for Collider::Circle, which is kept in line with it.
+/// Entities given a Collider of their own keep it instead.
 #[derive(Component)]
 pub struct Radius(pub f32);
 
+/// Marks a Collider as the circle made from Radius, so explicit ones are left alone.
+#[derive(Component)]
+struct FromRadius;
+
+/// Runs early, so that colliders are there for everything else this frame.
+#[system(Update::Early)]
+fn sync_radius_colliders(
+    mut radii: Query<(Entity, &Radius, Option<&mut Collider>, Has<FromRadius>), Changed<Radius>>,
+    mut commands: Commands,
+) {
+    radii
+        .iter_mut()
+        .for_each(|(entity, radius, collider, from_radius)| {
+            let circle = Collider::Circle { radius: radius.0 };
+            match collider {
+                None => {
+                    commands.entity(entity).insert((circle, FromRadius));
+                }
+                Some(mut collider) if from_radius => {
+                    collider.set_if_neq(circle);
+                }
+                Some(_) => {}
+            }
+        });
+}
+
 pub fn check_collision(
     radius: f32,
     translation: Vec2,
@@ -450,44 +433,13 @@ pub fn check_collision(
 }
 
 pub fn collide(
-    collider_grid: Res<ColliderGrid<GRID_WIDTH, GRID_HEIGHT>>,
-    colliders: Query<(Entity, &Radius, &Transform)>,
-    mut sensors: Query<(Entity, &Radius, &Transform, &mut CollisionSensor)>,
+    collider_grid: Res<ColliderGrid>,
+    colliders: Query<(Entity, &Collider, &Transform)>,
+    radii: Query<&Radius>,
     mut collision_questions: Query<(Entity, &mut CollisionQuestion)>,
     mut distance_questions: Query<(Entity, &mut DistanceSquaredBetweenEdgesQuestion)>,
     mut commands: Commands,
 ) {
-    sensors
-        .par_iter_mut()
-        .for_each(|(entity, collider, transform, mut sensor)| {
-            let Some(index) = collider_grid.translation_to_index(transform.translation.xy()) else {
-                return;
-            };
-
-            warn_once!("We don't call clear on the collisions??");
-
-            collider_grid.cells[index]
-                .0
-                .iter()
-                .for_each(|other_entity| {
-                    if entity == *other_entity {
-                        return;
-                    }
-
-                    let (other_entity, other_collider, other_transform) =
-                        colliders.get(*other_entity).unwrap();
-
-                    if check_collision(
-                        collider.0,
-                        transform.translation.xy(),
-                        other_collider.0,
-                        other_transform.translation.xy(),
-                    ) {
-                        sensor.collisions.add(other_entity);
-                    }
-                });
-        });
-
     collision_questions
         .iter_mut()
         .for_each(|(entity, mut question)| {
@@ -496,33 +448,29 @@ pub fn collide(
                 return;
             }
 
-            let Some(index) = collider_grid.translation_to_index(question.translation) else {
-                question.answer = Some(Collisions::default());
-                return;
-            };
+            let cell = collider_grid.translation_to_cell(question.translation);
 
             let mut collisions = Collisions::default();
 
-            collider_grid.cells[index]
-                .0
-                .iter()
-                .for_each(|other_entity| {
-                    if entity == *other_entity {
-                        return;
-                    }
+            collider_grid.cell(cell).iter().for_each(|other_entity| {
+                if entity == *other_entity {
+                    return;
+                }
 
-                    let (other_entity, other_collider, other_transform) =
-                        colliders.get(*other_entity).unwrap();
+                let Ok((other_entity, other_collider, other_transform)) =
+                    colliders.get(*other_entity)
+                else {
+                    return;
+                };
 
-                    if check_collision(
-                        question.radius,
-                        question.translation,
-                        other_collider.0,
-                        other_transform.translation.xy(),
-                    ) {
-                        collisions.add(other_entity);
-                    }
-                });
+                if other_collider.overlaps_circle(
+                    other_transform.translation.xy(),
+                    question.radius,
+                    question.translation,
+                ) {
+                    collisions.add(other_entity);
+                }
+            });
 
             question.answer = Some(collisions);
         });
@@ -535,40 +483,40 @@ pub fn collide(
                 return;
             }
 
-            let Some(index) = collider_grid.translation_to_index(question.translation) else {
-                question.answer = Some(default());
-                return;
-            };
+            let cell = collider_grid.translation_to_cell(question.translation);
 
             let mut collisions = CollisionsWithDistanceSquaredBetweenEdges::default();
 
-            collider_grid.cells[index]
-                .0
-                .iter()
-                .for_each(|other_entity| {
-                    if entity == *other_entity {
-                        return;
-                    }
+            collider_grid.cell(cell).iter().for_each(|other_entity| {
+                if entity == *other_entity {
+                    return;
+                }
+
+                let Ok((other_entity, _, other_transform)) = colliders.get(*other_entity) else {
+                    return;
+                };
 
-                    let (other_entity, other_collider, other_transform) =
-                        colliders.get(*other_entity).unwrap();
+                // Distance between edges only makes sense for circles.
+                let Ok(other_collider) = radii.get(other_entity) else {
+                    return;
+                };
 
-                    if check_collision(
-                        question.radius,
-                        question.translation,
-                        other_collider.0,
-                        other_transform.translation.xy(),
-                    ) {
-                        let distance_squared = question
-                            .translation
-                            .distance_squared(other_transform.translation.xy());
-
-                        let distance_squared_between_edges = distance_squared
-                            - (question.radius * question.radius)
-                            - (other_collider.0 * other_collider.0);
-                        collisions.add(other_entity, distance_squared_between_edges);
-                    }
-                });
+                if check_collision(
+                    question.radius,
+                    question.translation,
+                    other_collider.0,
+                    other_transform.translation.xy(),
+                ) {
+                    let distance_squared = question
+                        .translation
+                        .distance_squared(other_transform.translation.xy());
+
+                    let distance_squared_between_edges = distance_squared
+                        - (question.radius * question.radius)
+                        - (other_collider.0 * other_collider.0);
+                    collisions.add(other_entity, distance_squared_between_edges);
+                }
+            });
 
             question.answer = Some(collisions);
         });
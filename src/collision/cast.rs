@@ -0,0 +1,242 @@
+use bevy::{ecs::query::QueryFilter, utils::HashSet};
+
+use super::{ColliderGrid, ColliderQuery};
+use crate::prelude::*;
+
+pub mod prelude {
+    pub use super::Hit;
+}
+
+/// Where a ray or shapecast first touched a collider.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Hit {
+    pub entity: Entity,
+    /// The point on the collider that was touched.
+    pub point: Vec2,
+    /// The direction the collider's surface faces at the point.
+    pub normal: Vec2,
+    /// How far along the direction the ray or shape travelled before touching.
+    pub distance: f32,
+}
+
+impl ColliderGrid {
+    /// The first collider the ray hits, for line of sight and picking.
+    /// Only entities the layers collide with, and the filter returns true for, can be hit.
+    /// The direction doesn't need to be normalised.
+    pub fn raycast<T: QueryFilter>(
+        &self,
+        origin: Vec2,
+        direction: Vec2,
+        max_distance: f32,
+        layers: CollisionLayers,
+        filter: impl Fn(Entity) -> bool,
+        colliders: &ColliderQuery<T>,
+    ) -> Option<Hit> {
+        self.shapecast(
+            origin,
+            0.,
+            direction,
+            max_distance,
+            layers,
+            filter,
+            colliders,
+        )
+    }
+
+    /// Every collider the ray hits, nearest first.
+    pub fn raycast_all<T: QueryFilter>(
+        &self,
+        origin: Vec2,
+        direction: Vec2,
+        max_distance: f32,
+        layers: CollisionLayers,
+        filter: impl Fn(Entity) -> bool,
+        colliders: &ColliderQuery<T>,
+    ) -> Vec<Hit> {
+        let Some(direction) = direction.try_normalize() else {
+            return vec![];
+        };
+
+        let mut checked = HashSet::new();
+        let mut hits = vec![];
+        self.walk(origin, direction, max_distance, |cell, _| {
+            self.cell(cell).iter().for_each(|entity| {
+                if !checked.insert(*entity) || !filter(*entity) {
+                    return;
+                }
+                hits.extend(Self::cast_against(
+                    *entity,
+                    origin,
+                    0.,
+                    direction,
+                    max_distance,
+                    layers,
+                    colliders,
+                ));
+            });
+            true
+        });
+
+        hits.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+        hits
+    }
+
+    /// The first collider a circle moving from the origin hits, such as for ground probes.
+    /// The radius must be smaller than a cell, like any other query.
+    #[allow(clippy::too_many_arguments)]
+    pub fn shapecast<T: QueryFilter>(
+        &self,
+        origin: Vec2,
+        radius: f32,
+        direction: Vec2,
+        max_distance: f32,
+        layers: CollisionLayers,
+        filter: impl Fn(Entity) -> bool,
+        colliders: &ColliderQuery<T>,
+    ) -> Option<Hit> {
+        let direction = direction.try_normalize()?;
+
+        let mut checked = HashSet::new();
+        let mut nearest: Option<Hit> = None;
+        self.walk(origin, direction, max_distance, |cell, entered| {
+            // Anything in a later cell is hit further away than this.
+            if nearest.is_some_and(|nearest| nearest.distance < entered) {
+                return false;
+            }
+
+            self.cell(cell).iter().for_each(|entity| {
+                if !checked.insert(*entity) || !filter(*entity) {
+                    return;
+                }
+                let Some(hit) = Self::cast_against(
+                    *entity,
+                    origin,
+                    radius,
+                    direction,
+                    max_distance,
+                    layers,
+                    colliders,
+                ) else {
+                    return;
+                };
+                if nearest.is_none_or(|nearest| hit.distance < nearest.distance) {
+                    nearest = Some(hit);
+                }
+            });
+            true
+        });
+
+        nearest
+    }
+
+    fn cast_against<T: QueryFilter>(
+        entity: Entity,
+        origin: Vec2,
+        radius: f32,
+        direction: Vec2,
+        max_distance: f32,
+        layers: CollisionLayers,
+        colliders: &ColliderQuery<T>,
+    ) -> Option<Hit> {
+        let (collider, transform, other_layers) = colliders.get(entity).ok()?;
+        if !layers.interacts(&other_layers.copied().unwrap_or_default()) {
+            return None;
+        }
+
+        let (distance, normal) = collider.cast(
+            transform.translation.xy(),
+            origin,
+            direction,
+            max_distance,
+            radius,
+        )?;
+
+        Some(Hit {
+            entity,
+            point: origin + direction * distance - normal * radius,
+            normal,
+            distance,
+        })
+    }
+
+    /// Visits every cell the line passes through in order, along with how far along the line it was entered.
+    /// Stops once the line ends, leaves every occupied cell, or visit returns false.
+    /// This is the DDA from "A Fast Voxel Traversal Algorithm for Ray Tracing" by Amanatides and Woo.
+    fn walk(
+        &self,
+        origin: Vec2,
+        direction: Vec2,
+        max_distance: f32,
+        mut visit: impl FnMut(IVec2, f32) -> bool,
+    ) {
+        let Some(occupied) = self.occupied else {
+            return;
+        };
+
+        // Past the last occupied cell there is nothing to hit, so rays of any length end.
+        let bounds = Rect {
+            min: occupied.min.as_vec2() * self.cell_size,
+            max: (occupied.max + 1).as_vec2() * self.cell_size,
+        };
+        let max_distance = max_distance.min(distance_to_leave(origin, direction, bounds));
+
+        let mut cell = self.translation_to_cell(origin);
+        let step = direction.signum().as_ivec2();
+        let mut next_boundary = Vec2::ZERO;
+        let mut boundary_delta = Vec2::ZERO;
+
+        for axis in 0..2 {
+            if direction[axis] == 0. {
+                next_boundary[axis] = f32::INFINITY;
+                boundary_delta[axis] = f32::INFINITY;
+                continue;
+            }
+
+            let boundary = if step[axis] > 0 {
+                (cell[axis] + 1) as f32
+            } else {
+                cell[axis] as f32
+            } * self.cell_size[axis];
+            next_boundary[axis] = (boundary - origin[axis]) / direction[axis];
+            boundary_delta[axis] = self.cell_size[axis] / direction[axis].abs();
+        }
+
+        let mut entered = 0.;
+        while entered <= max_distance && visit(cell, entered) {
+            let axis = if next_boundary.x < next_boundary.y {
+                0
+            } else {
+                1
+            };
+            entered = next_boundary[axis];
+            cell[axis] += step[axis];
+            next_boundary[axis] += boundary_delta[axis];
+        }
+    }
+}
+
+/// How far along the line it is until it leaves the bounds, or 0 if it never enters them.
+fn distance_to_leave(origin: Vec2, direction: Vec2, bounds: Rect) -> f32 {
+    let mut entered = 0_f32;
+    let mut exited = f32::INFINITY;
+
+    for axis in 0..2 {
+        if direction[axis] == 0. {
+            if origin[axis] < bounds.min[axis] || origin[axis] > bounds.max[axis] {
+                return 0.;
+            }
+            continue;
+        }
+
+        let first = (bounds.min[axis] - origin[axis]) / direction[axis];
+        let second = (bounds.max[axis] - origin[axis]) / direction[axis];
+        entered = entered.max(first.min(second));
+        exited = exited.min(first.max(second));
+    }
+
+    if entered > exited {
+        0.
+    } else {
+        exited
+    }
+}
@@ -0,0 +1,506 @@
+use crate::prelude::*;
+
+pub mod prelude {
+    pub use super::{Collider, Contact};
+}
+
+/// The shape of anything that collides, positioned by its Transform's translation. Rotation and scale are ignored.
+/// Radius is shorthand for a circle, and keeps one of these in line with it for you.
+#[derive(Component, Clone, Copy, PartialEq, Debug)]
+pub enum Collider {
+    Circle {
+        radius: f32,
+    },
+    /// A box that always lines up with the axes.
+    Aabb {
+        half_size: Vec2,
+    },
+    /// A segment with a radius around it. Start and end are relative to the translation.
+    Capsule {
+        start: Vec2,
+        end: Vec2,
+        radius: f32,
+    },
+    /// A line with no thickness. Start and end are relative to the translation.
+    Segment {
+        start: Vec2,
+        end: Vec2,
+    },
+}
+
+/// How 2 colliders overlap.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Contact {
+    /// The direction to move the first collider in to separate them.
+    pub normal: Vec2,
+    /// How far the first collider has to move along the normal to separate them. 0 if they are only touching.
+    pub depth: f32,
+}
+
+impl Contact {
+    /// The same contact, from the point of view of the other collider.
+    pub fn flipped(self) -> Self {
+        Self {
+            normal: -self.normal,
+            depth: self.depth,
+        }
+    }
+}
+
+/// Every collider is either a box, or a segment with a radius around it. Circles are segments with no length.
+/// This keeps the number of pairs that need their own overlap test down to 3.
+#[derive(Clone, Copy)]
+enum Shape {
+    Box { min: Vec2, max: Vec2 },
+    Rounded { start: Vec2, end: Vec2, radius: f32 },
+}
+
+impl Collider {
+    fn shape(&self, translation: Vec2) -> Shape {
+        match *self {
+            Self::Circle { radius } => Shape::Rounded {
+                start: translation,
+                end: translation,
+                radius,
+            },
+            Self::Aabb { half_size } => Shape::Box {
+                min: translation - half_size,
+                max: translation + half_size,
+            },
+            Self::Capsule { start, end, radius } => Shape::Rounded {
+                start: translation + start,
+                end: translation + end,
+                radius,
+            },
+            Self::Segment { start, end } => Shape::Rounded {
+                start: translation + start,
+                end: translation + end,
+                radius: 0.,
+            },
+        }
+    }
+
+    /// The smallest box around the collider.
+    pub fn bounds(&self, translation: Vec2) -> Rect {
+        match self.shape(translation) {
+            Shape::Box { min, max } => Rect::from_corners(min, max),
+            Shape::Rounded { start, end, radius } => Rect::from_corners(start, end).inflate(radius),
+        }
+    }
+
+    pub fn overlaps(&self, translation: Vec2, other: &Collider, other_translation: Vec2) -> bool {
+        self.contact(translation, other, other_translation)
+            .is_some()
+    }
+
+    /// Shorthand for checking against a circle, which is what most queries are.
+    pub fn overlaps_circle(
+        &self,
+        translation: Vec2,
+        radius: f32,
+        circle_translation: Vec2,
+    ) -> bool {
+        Collider::Circle { radius }.overlaps(circle_translation, self, translation)
+    }
+
+    /// How the collider overlaps the other, if it does. Touching counts as overlapping, with a depth of 0.
+    pub fn contact(
+        &self,
+        translation: Vec2,
+        other: &Collider,
+        other_translation: Vec2,
+    ) -> Option<Contact> {
+        // Used when the shapes are centred on each other, so there is no better direction to separate them in.
+        let fallback_normal = (translation - other_translation).normalize_or(Vec2::Y);
+
+        match (self.shape(translation), other.shape(other_translation)) {
+            (
+                Shape::Box { min, max },
+                Shape::Box {
+                    min: other_min,
+                    max: other_max,
+                },
+            ) => box_box_contact(min, max, other_min, other_max, fallback_normal),
+            (Shape::Box { min, max }, Shape::Rounded { start, end, radius }) => {
+                box_rounded_contact(min, max, start, end, radius, fallback_normal)
+            }
+            (Shape::Rounded { start, end, radius }, Shape::Box { min, max }) => {
+                box_rounded_contact(min, max, start, end, radius, -fallback_normal)
+                    .map(Contact::flipped)
+            }
+            (
+                Shape::Rounded { start, end, radius },
+                Shape::Rounded {
+                    start: other_start,
+                    end: other_end,
+                    radius: other_radius,
+                },
+            ) => rounded_rounded_contact(
+                (start, end, radius),
+                (other_start, other_end, other_radius),
+                fallback_normal,
+            ),
+        }
+    }
+
+    /// How far a circle moving from the origin along the direction gets before touching the collider,
+    /// and the normal of the collider where it touches. A radius of 0 casts a ray.
+    /// The direction must be normalised. A circle that starts overlapping is hit straight away, with the normal facing back along the direction.
+    pub fn cast(
+        &self,
+        translation: Vec2,
+        origin: Vec2,
+        direction: Vec2,
+        max_distance: f32,
+        radius: f32,
+    ) -> Option<(f32, Vec2)> {
+        if self.overlaps_circle(translation, radius, origin) {
+            return Some((0., -direction));
+        }
+
+        // Casting a circle is the same as casting a ray against the collider grown by the circle's radius.
+        let hit = match self.shape(translation) {
+            Shape::Box { min, max } => {
+                if radius == 0. {
+                    ray_box(origin, direction, min, max)
+                } else {
+                    let corners = [min, Vec2::new(max.x, min.y), max, Vec2::new(min.x, max.y)];
+                    corners
+                        .into_iter()
+                        .filter_map(|corner| ray_circle(origin, direction, corner, radius))
+                        .chain(ray_box(
+                            origin,
+                            direction,
+                            min - Vec2::X * radius,
+                            max + Vec2::X * radius,
+                        ))
+                        .chain(ray_box(
+                            origin,
+                            direction,
+                            min - Vec2::Y * radius,
+                            max + Vec2::Y * radius,
+                        ))
+                        .min_by(|(a, _), (b, _)| a.total_cmp(b))
+                }
+            }
+            Shape::Rounded {
+                start,
+                end,
+                radius: shape_radius,
+            } => ray_rounded(origin, direction, start, end, shape_radius + radius),
+        };
+
+        hit.filter(|(distance, _)| *distance <= max_distance)
+    }
+
+    pub fn draw(&self, gizmos: &mut Gizmos, translation: Vec2, color: impl Into<Color>) {
+        let color = color.into();
+        match self.shape(translation) {
+            Shape::Box { min, max } => {
+                gizmos.rect_2d((min + max) * 0.5, max - min, color);
+            }
+            Shape::Rounded { start, end, radius } => {
+                if radius == 0. {
+                    gizmos.line_2d(start, end, color);
+                    return;
+                }
+
+                gizmos.circle_2d(start, radius, color);
+                if start != end {
+                    gizmos.circle_2d(end, radius, color);
+                    let side = (end - start).perp().normalize() * radius;
+                    gizmos.line_2d(start + side, end + side, color);
+                    gizmos.line_2d(start - side, end - side, color);
+                }
+            }
+        }
+    }
+}
+
+fn box_box_contact(
+    min: Vec2,
+    max: Vec2,
+    other_min: Vec2,
+    other_max: Vec2,
+    fallback_normal: Vec2,
+) -> Option<Contact> {
+    let overlap = max.min(other_max) - min.max(other_min);
+    if overlap.x < 0. || overlap.y < 0. {
+        return None;
+    }
+
+    // Separated along whichever axis needs the least movement.
+    let direction = (min + max) - (other_min + other_max);
+    let normal = if overlap.x < overlap.y {
+        Vec2::X * sign_or(direction.x, fallback_normal.x)
+    } else {
+        Vec2::Y * sign_or(direction.y, fallback_normal.y)
+    };
+
+    Some(Contact {
+        normal,
+        depth: overlap.x.min(overlap.y),
+    })
+}
+
+/// The contact is for the box.
+fn box_rounded_contact(
+    min: Vec2,
+    max: Vec2,
+    start: Vec2,
+    end: Vec2,
+    radius: f32,
+    fallback_normal: Vec2,
+) -> Option<Contact> {
+    let corners = [min, Vec2::new(max.x, min.y), max, Vec2::new(min.x, max.y)];
+
+    if !segment_intersects_box(start, end, min, max) {
+        // The closest points of a segment and a box that don't intersect are always at a corner of the box, or an end of the segment.
+        let (on_box, on_segment) = corners
+            .into_iter()
+            .map(|corner| (corner, closest_on_segment(corner, start, end)))
+            .chain([start, end].map(|point| (point.clamp(min, max), point)))
+            .min_by(|(a, b), (c, d)| a.distance_squared(*b).total_cmp(&c.distance_squared(*d)))
+            .unwrap();
+
+        let distance = on_box.distance(on_segment);
+        if distance > radius {
+            return None;
+        }
+
+        return Some(Contact {
+            normal: (on_box - on_segment).normalize_or(fallback_normal),
+            depth: radius - distance,
+        });
+    }
+
+    // The segment crosses into the box, so separate along whichever axis needs the least movement.
+    let mut axes = vec![Vec2::X, Vec2::Y];
+    if start != end {
+        axes.push((end - start).perp().normalize());
+    }
+    let centre = (min + max) * 0.5;
+
+    axes.into_iter()
+        .map(|axis| {
+            let (box_min, box_max) = project(&corners, axis);
+            let (segment_min, segment_max) = project(&[start, end], axis);
+            let overlap = box_max.min(segment_max + radius) - box_min.max(segment_min - radius);
+            let direction = centre.dot(axis) - (start + end).dot(axis) * 0.5;
+            Contact {
+                normal: axis * sign_or(direction, fallback_normal.dot(axis)),
+                depth: overlap,
+            }
+        })
+        .min_by(|a, b| a.depth.total_cmp(&b.depth))
+}
+
+fn rounded_rounded_contact(
+    (start, end, radius): (Vec2, Vec2, f32),
+    (other_start, other_end, other_radius): (Vec2, Vec2, f32),
+    fallback_normal: Vec2,
+) -> Option<Contact> {
+    let (closest, other_closest) = closest_between_segments(start, end, other_start, other_end);
+    let distance = closest.distance(other_closest);
+    let radius_sum = radius + other_radius;
+
+    if distance > radius_sum {
+        return None;
+    }
+
+    // Crossing segments have no distance between them, so they are pushed off the other's line instead.
+    let normal = if distance > 0. {
+        (closest - other_closest) / distance
+    } else if other_start != other_end {
+        let perpendicular = (other_end - other_start).perp().normalize();
+        perpendicular * sign_or(perpendicular.dot(fallback_normal), 1.)
+    } else {
+        fallback_normal
+    };
+
+    Some(Contact {
+        normal,
+        depth: radius_sum - distance,
+    })
+}
+
+/// Where the ray first enters the box. Rays that start inside aren't hits.
+fn ray_box(origin: Vec2, direction: Vec2, min: Vec2, max: Vec2) -> Option<(f32, Vec2)> {
+    let mut entered = f32::NEG_INFINITY;
+    let mut exited = f32::INFINITY;
+    let mut normal = Vec2::ZERO;
+
+    for axis in 0..2 {
+        if direction[axis] == 0. {
+            if origin[axis] < min[axis] || origin[axis] > max[axis] {
+                return None;
+            }
+            continue;
+        }
+
+        let first = (min[axis] - origin[axis]) / direction[axis];
+        let second = (max[axis] - origin[axis]) / direction[axis];
+        if first.min(second) > entered {
+            entered = first.min(second);
+            normal = Vec2::ZERO;
+            normal[axis] = -direction[axis].signum();
+        }
+        exited = exited.min(first.max(second));
+    }
+
+    (entered >= 0. && entered <= exited).then_some((entered, normal))
+}
+
+/// Where the ray first enters the circle. Rays that start inside aren't hits.
+fn ray_circle(origin: Vec2, direction: Vec2, centre: Vec2, radius: f32) -> Option<(f32, Vec2)> {
+    let offset = origin - centre;
+    let half_b = offset.dot(direction);
+    let discriminant = half_b * half_b - (offset.length_squared() - radius * radius);
+    if discriminant < 0. {
+        return None;
+    }
+
+    let distance = -half_b - discriminant.sqrt();
+    if distance < 0. {
+        return None;
+    }
+
+    let normal = (origin + direction * distance - centre).normalize_or(-direction);
+    Some((distance, normal))
+}
+
+/// Where the ray first crosses the segment, from either side.
+fn ray_segment(origin: Vec2, direction: Vec2, start: Vec2, end: Vec2) -> Option<(f32, Vec2)> {
+    let along = end - start;
+    let denominator = direction.perp_dot(along);
+    // Parallel rays never cross it.
+    if denominator == 0. {
+        return None;
+    }
+
+    let offset = start - origin;
+    let distance = offset.perp_dot(along) / denominator;
+    let fraction = offset.perp_dot(direction) / denominator;
+    if distance < 0. || !(0. ..=1.).contains(&fraction) {
+        return None;
+    }
+
+    let normal = along.perp().normalize();
+    Some((distance, normal * -sign_or(normal.dot(direction), 1.)))
+}
+
+/// Where the ray first enters the segment grown by the radius.
+fn ray_rounded(
+    origin: Vec2,
+    direction: Vec2,
+    start: Vec2,
+    end: Vec2,
+    radius: f32,
+) -> Option<(f32, Vec2)> {
+    if radius == 0. {
+        return ray_segment(origin, direction, start, end);
+    }
+
+    let mut hits = vec![ray_circle(origin, direction, start, radius)];
+    if start != end {
+        let side = (end - start).perp().normalize() * radius;
+        hits.push(ray_circle(origin, direction, end, radius));
+        hits.push(ray_segment(origin, direction, start + side, end + side));
+        hits.push(ray_segment(origin, direction, start - side, end - side));
+    }
+
+    hits.into_iter()
+        .flatten()
+        .min_by(|(a, _), (b, _)| a.total_cmp(b))
+}
+
+/// The sign of the value, or of the fallback if the value is 0.
+fn sign_or(value: f32, fallback: f32) -> f32 {
+    if value != 0. {
+        value.signum()
+    } else if fallback != 0. {
+        fallback.signum()
+    } else {
+        1.
+    }
+}
+
+/// The lowest and highest of the points along the axis.
+fn project(points: &[Vec2], axis: Vec2) -> (f32, f32) {
+    points.iter().map(|point| point.dot(axis)).fold(
+        (f32::INFINITY, f32::NEG_INFINITY),
+        |(min, max), projected| (min.min(projected), max.max(projected)),
+    )
+}
+
+/// The point on the segment closest to the point.
+fn closest_on_segment(point: Vec2, start: Vec2, end: Vec2) -> Vec2 {
+    let along = end - start;
+    let length_squared = along.length_squared();
+    if length_squared == 0. {
+        return start;
+    }
+
+    let fraction = ((point - start).dot(along) / length_squared).clamp(0., 1.);
+    start + along * fraction
+}
+
+/// The closest points between 2 segments, on the first and then on the second.
+fn closest_between_segments(
+    start: Vec2,
+    end: Vec2,
+    other_start: Vec2,
+    other_end: Vec2,
+) -> (Vec2, Vec2) {
+    let along = end - start;
+    let other_along = other_end - other_start;
+    let denominator = along.perp_dot(other_along);
+
+    // Segments that cross meet at a single point.
+    if denominator != 0. {
+        let offset = other_start - start;
+        let fraction = offset.perp_dot(other_along) / denominator;
+        let other_fraction = offset.perp_dot(along) / denominator;
+        if (0. ..=1.).contains(&fraction) && (0. ..=1.).contains(&other_fraction) {
+            let point = start + along * fraction;
+            return (point, point);
+        }
+    }
+
+    // Otherwise an end of one of them is always 1 of the closest points.
+    [
+        (start, closest_on_segment(start, other_start, other_end)),
+        (end, closest_on_segment(end, other_start, other_end)),
+        (closest_on_segment(other_start, start, end), other_start),
+        (closest_on_segment(other_end, start, end), other_end),
+    ]
+    .into_iter()
+    .min_by(|(a, b), (c, d)| a.distance_squared(*b).total_cmp(&c.distance_squared(*d)))
+    .unwrap()
+}
+
+/// Whether any part of the segment is inside the box.
+fn segment_intersects_box(start: Vec2, end: Vec2, min: Vec2, max: Vec2) -> bool {
+    let along = end - start;
+    let mut entered = 0_f32;
+    let mut exited = 1_f32;
+
+    for axis in 0..2 {
+        if along[axis] == 0. {
+            if start[axis] < min[axis] || start[axis] > max[axis] {
+                return false;
+            }
+            continue;
+        }
+
+        let first = (min[axis] - start[axis]) / along[axis];
+        let second = (max[axis] - start[axis]) / along[axis];
+        entered = entered.max(first.min(second));
+        exited = exited.min(first.max(second));
+        if entered > exited {
+            return false;
+        }
+    }
+
+    true
+}
@@ -0,0 +1,42 @@
+use crate::prelude::*;
+
+pub mod prelude {
+    pub use super::CollisionLayers;
+}
+
+/// Which colliders can touch which, so suns can pass through plants but still stop on terrain.
+/// 2 colliders only collide if each is a member of a layer in the other's filter.
+/// Colliders without this are a member of DEFAULT, and collide with everything.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct CollisionLayers {
+    /// The layers this is a member of, 1 bit each.
+    pub membership: u32,
+    /// The layers this collides with, 1 bit each.
+    pub filter: u32,
+}
+
+impl Default for CollisionLayers {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT, Self::ALL)
+    }
+}
+
+impl CollisionLayers {
+    pub const NONE: u32 = 0;
+    pub const ALL: u32 = u32::MAX;
+
+    pub const DEFAULT: u32 = 1 << 0;
+    pub const TERRAIN: u32 = 1 << 1;
+    pub const PLAYER: u32 = 1 << 2;
+    pub const SUN: u32 = 1 << 3;
+    pub const PLANT: u32 = 1 << 4;
+
+    pub const fn new(membership: u32, filter: u32) -> Self {
+        Self { membership, filter }
+    }
+
+    /// Whether colliders with these layers collide. Works both ways, so the order doesn't matter.
+    pub fn interacts(&self, other: &Self) -> bool {
+        self.filter & other.membership != 0 && other.filter & self.membership != 0
+    }
+}
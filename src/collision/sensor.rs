@@ -0,0 +1,190 @@
+use bevy::ecs::{component::ComponentId, world::DeferredWorld};
+
+use super::{ColliderGrid, ColliderQuery};
+use crate::prelude::*;
+
+pub mod prelude {
+    pub use super::{CollisionEnded, CollisionOngoing, CollisionSensor, CollisionStarted};
+}
+
+/// Keeps track of everything its Collider touches, and sends events whenever that changes.
+/// Like any other query on the grid, it must be smaller than a cell.
+#[derive(Component, Default)]
+#[component(on_remove = CollisionSensor::end_all)]
+pub struct CollisionSensor {
+    /// Also send CollisionOngoing every physics update, for everything that is still being touched.
+    pub send_ongoing: bool,
+    /// Everything touched as of the last physics update.
+    contacts: HashMap<Entity, Contact>,
+}
+
+impl CollisionSensor {
+    pub fn new(send_ongoing: bool) -> Self {
+        Self {
+            send_ongoing,
+            contacts: HashMap::default(),
+        }
+    }
+
+    /// Everything the sensor is touching, and how.
+    pub fn iter(&self) -> impl Iterator<Item = (Entity, &Contact)> {
+        self.contacts
+            .iter()
+            .map(|(entity, contact)| (*entity, contact))
+    }
+
+    pub fn is_touching(&self, entity: Entity) -> bool {
+        self.contacts.contains_key(&entity)
+    }
+
+    /// Nothing is touched once the sensor is gone, so everything it was touching ends.
+    fn end_all(mut world: DeferredWorld, entity: Entity, _: ComponentId) {
+        let Some(sensor) = world.get::<CollisionSensor>(entity) else {
+            return;
+        };
+        let ended = sensor
+            .iter()
+            .map(|(other, contact)| CollisionEnded {
+                sensor: entity,
+                other,
+                contact: *contact,
+            })
+            .collect::<Vec<_>>();
+        world.send_event_batch(ended);
+    }
+}
+
+/// Sent when a sensor starts touching something.
+#[init]
+#[derive(Event, Clone, Copy, Debug)]
+pub struct CollisionStarted {
+    pub sensor: Entity,
+    pub other: Entity,
+    /// From the sensor's point of view.
+    pub contact: Contact,
+}
+
+/// Sent every physics update while a sensor is still touching something, if it has send_ongoing set.
+/// Not sent on the update it started touching.
+#[init]
+#[derive(Event, Clone, Copy, Debug)]
+pub struct CollisionOngoing {
+    pub sensor: Entity,
+    pub other: Entity,
+    /// From the sensor's point of view.
+    pub contact: Contact,
+}
+
+/// Sent when a sensor stops touching something, including when either is despawned.
+#[init]
+#[derive(Event, Clone, Copy, Debug)]
+pub struct CollisionEnded {
+    pub sensor: Entity,
+    pub other: Entity,
+    /// The last contact they had, from the sensor's point of view.
+    pub contact: Contact,
+}
+
+/// What changed for a sensor, so events can be collected in parallel and sent afterwards.
+enum SensorChange {
+    Started(CollisionStarted),
+    Ongoing(CollisionOngoing),
+    Ended(CollisionEnded),
+}
+
+/// Finds what every sensor is touching, and sends events for whatever changed since the last physics update.
+#[system(Update::Physics::Sensors)]
+fn sense(
+    mut sensors: Query<(
+        Entity,
+        &mut CollisionSensor,
+        &Collider,
+        &Transform,
+        Option<&CollisionLayers>,
+    )>,
+    colliders: ColliderQuery,
+    grid: Res<ColliderGrid>,
+    mut started: EventWriter<CollisionStarted>,
+    mut ongoing: EventWriter<CollisionOngoing>,
+    mut ended: EventWriter<CollisionEnded>,
+    mut changes: Local<Parallel<Vec<SensorChange>>>,
+) {
+    sensors
+        .par_iter_mut()
+        .for_each(|(entity, mut sensor, collider, transform, layers)| {
+            let translation = transform.translation.xy();
+            let layers = layers.copied().unwrap_or_default();
+            let mut changes = changes.borrow_local_mut();
+
+            let mut contacts = HashMap::default();
+            grid.cell(grid.translation_to_cell(translation))
+                .iter()
+                .for_each(|other| {
+                    if entity == *other {
+                        return;
+                    }
+
+                    let Ok((other_collider, other_transform, other_layers)) = colliders.get(*other)
+                    else {
+                        return;
+                    };
+
+                    if !layers.interacts(&other_layers.copied().unwrap_or_default()) {
+                        return;
+                    }
+
+                    let Some(contact) = collider.contact(
+                        translation,
+                        other_collider,
+                        other_transform.translation.xy(),
+                    ) else {
+                        return;
+                    };
+
+                    contacts.insert(*other, contact);
+                });
+
+            contacts.iter().for_each(|(other, contact)| {
+                let (other, contact) = (*other, *contact);
+                if !sensor.contacts.contains_key(&other) {
+                    changes.push(SensorChange::Started(CollisionStarted {
+                        sensor: entity,
+                        other,
+                        contact,
+                    }));
+                } else if sensor.send_ongoing {
+                    changes.push(SensorChange::Ongoing(CollisionOngoing {
+                        sensor: entity,
+                        other,
+                        contact,
+                    }));
+                }
+            });
+
+            sensor.contacts.iter().for_each(|(other, contact)| {
+                if !contacts.contains_key(other) {
+                    changes.push(SensorChange::Ended(CollisionEnded {
+                        sensor: entity,
+                        other: *other,
+                        contact: *contact,
+                    }));
+                }
+            });
+
+            sensor.contacts = contacts;
+        });
+
+    changes.iter_mut().for_each(|changes| {
+        changes.drain(..).for_each(|change| match change {
+            SensorChange::Started(event) => {
+                started.send(event);
+            }
+            SensorChange::Ongoing(event) => {
+                ongoing.send(event);
+            }
+            SensorChange::Ended(event) => {
+                ended.send(event);
+            }
+        });
+    });
+}
@@ -6,27 +6,21 @@ pub mod prelude {
 
 /// Stores anything needed for the general editor, which is the right panel.
 #[init]
-#[derive(Resource)]
+#[derive(Resource, SaveAndLoadResource, Default)]
+#[save(path = "./map")]
 pub struct Editor {
     category_open: usize,
     /// Used for copying and pasting.
     /// This also is set when you select an item from the right panel.
     /// The &str is so we can only show the paste button when the &str is equal to the expected value.
-    pub copied: (fn(&mut Commands, &AssetServer, Vec2), &'static str),
+    /// None until something is selected.
+    #[save(skip)]
+    pub copied: Option<(fn(&mut Commands, &AssetServer, Vec2), &'static str)>,
 
+    #[save(map)]
     pub selected_entities: Vec<Entity>,
 }
 
-impl Default for Editor {
-    fn default() -> Self {
-        Self {
-            category_open: 0,
-            copied: (|_, _, _| {}, ""),
-            selected_entities: vec![],
-        }
-    }
-}
-
 impl Editor {
     // I want a long list of buttons. When you click on one it selects it. From there you can click and it will spawn one wherever you click.
     pub fn ui(mut contexts: EguiContexts, mut editor: ResMut<Editor>) {
@@ -79,6 +73,10 @@ impl Editor {
                                     )),
                                     Verlet::from_translation(translation),
                                     Radius(15.),
+                                    CollisionLayers::new(
+                                        CollisionLayers::PLANT,
+                                        CollisionLayers::ALL,
+                                    ),
                                     Gravity,
                                 ));
                             }
@@ -100,9 +98,9 @@ impl Editor {
                 ui.add_space(10.);
                 let button = ui.button(*name);
                 if button.clicked() {
-                    editor.copied = (*spawner, *name);
+                    editor.copied = Some((*spawner, *name));
                 }
-                if *name == editor.copied.1 {
+                if editor.copied.is_some_and(|(_, copied)| copied == *name) {
                     button.highlight();
                 }
             });
@@ -117,8 +115,11 @@ impl Editor {
         mut commands: Commands,
         asset_server: Res<AssetServer>,
     ) {
-        if actions.just_pressed(&Action::EditorCreate) {
-            (editor.copied.0)(&mut commands, &asset_server, translation.0);
+        if let Some((spawner, _)) = editor
+            .copied
+            .filter(|_| actions.just_pressed(&Action::EditorCreate))
+        {
+            spawner(&mut commands, &asset_server, translation.0);
         }
     }
 }
@@ -1,3 +1,5 @@
+use bevy::ecs::{component::ComponentId, world::DeferredWorld};
+
 use crate::prelude::*;
 
 fn ui_background() -> Color {
@@ -103,7 +105,7 @@ fn setup(mut menu: MenuReader, mut load: Load, mut commands: Commands) {
                 },
             },
         ]),
-        PlantTester { lines: vec![] },
+        PlantTester::default(),
     ));
 
     // For testing purposes.
@@ -115,8 +117,10 @@ fn setup(mut menu: MenuReader, mut load: Load, mut commands: Commands) {
 #[system(Update)]
 fn debug_plants(
     mut plants: Query<(&Transform, &mut Plant, &mut PlantTester)>,
+    mut colliders: Query<(&mut Transform, &mut Collider), Without<PlantTester>>,
     mut gizmos: Gizmos,
     time: Res<Time>,
+    mut commands: Commands,
 ) {
     const SPEED: f32 = 10.;
     let time_delta_seconds = time.delta_secs();
@@ -201,12 +205,62 @@ fn debug_plants(
                     true
                 }
             });
+
+            // Every line collides as a segment, moved along with it as it grows.
+            // After the instructions, so that lines added this frame collide straight away.
+            let plant_tester = &mut *plant_tester;
+            plant_tester.lines.iter().enumerate().for_each(
+                |(index, (translation_1, translation_2))| {
+                    let line_transform = Transform::from_translation(transform.translation);
+                    let line_collider = Collider::Segment {
+                        start: *translation_1,
+                        end: *translation_2,
+                    };
+
+                    // Only written when they differ, so unmoved lines aren't marked as changed.
+                    if let Some(entity) = plant_tester.colliders.get(index) {
+                        if let Ok((mut collider_transform, mut collider)) =
+                            colliders.get_mut(*entity)
+                        {
+                            collider_transform.set_if_neq(line_transform);
+                            collider.set_if_neq(line_collider);
+                        }
+                        return;
+                    }
+
+                    plant_tester.colliders.push(
+                        commands
+                            .spawn((
+                                line_transform,
+                                line_collider,
+                                CollisionLayers::new(CollisionLayers::PLANT, CollisionLayers::ALL),
+                            ))
+                            .id(),
+                    );
+                },
+            );
         });
 }
 
 #[derive(Component, Default)]
+#[component(on_remove = PlantTester::despawn_colliders)]
 struct PlantTester {
     lines: Vec<(Vec2, Vec2)>,
+    /// The collider of each line. Not children, as colliders are positioned by their Transform.
+    colliders: Vec<Entity>,
+}
+
+impl PlantTester {
+    /// The colliders are only there for the lines, so they go with them.
+    fn despawn_colliders(mut world: DeferredWorld, entity: Entity, _: ComponentId) {
+        let Some(plant_tester) = world.get::<PlantTester>(entity) else {
+            return;
+        };
+        let colliders = plant_tester.colliders.clone();
+        colliders.into_iter().for_each(|collider| {
+            world.commands().entity(collider).despawn();
+        });
+    }
 }
 
 #[derive(Component)]
@@ -4,7 +4,8 @@ pub mod prelude {
     pub use super::Language;
 }
 
-#[derive(Resource, Clone)]
+#[derive(Resource, Clone, SaveAndLoadResource)]
+#[save(path = "./profiles")]
 pub enum Language {
     English,
     // Idea: AutomaticTranslation(something idk),
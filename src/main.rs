@@ -82,7 +82,7 @@ mod prelude {
     pub use std::{
         any::TypeId,
         fs::{self, File},
-        path::Path,
+        path::{Path, PathBuf},
         sync::Arc,
         time::Duration,
     };
@@ -114,6 +114,7 @@ schedule! {
             Chain,
             CollisionResolution,
             SyncPositions,
+            Sensors,
         ),
         SaveAndLoad,
     )
@@ -141,6 +142,7 @@ fn main() {
             //LogDiagnosticsPlugin::default(),
             InputManagerPlugin::<Action>::default(),
             RunEveryPlugin,
+            SavingPlugin,
             EguiPlugin,
             RegistrationPlugin,
             TextEditPluginNoState,
@@ -152,7 +154,7 @@ fn main() {
                 (PlantCell::update),
                 (
                     LineSelected::ui,
-                    TerrainLine::on_load,
+                    TerrainLine::on_load.before(TerrainLine::generate),
                     TerrainLine::generate,
                     TerrainLine::validate,
                     TerrainLine::debug,
@@ -206,7 +208,7 @@ fn main() {
         .init_resource::<CursorWorldTranslation>()
         .init_resource::<CursorPreviousWorldTranslation>()
         .init_resource::<LineSelected>()
-        .insert_resource(ColliderGrid::new(GRID_ORIGIN))
+        .init_resource::<ColliderGrid>()
         .run();
 }
 
@@ -227,6 +229,7 @@ fn setup(mut commands: Commands, asset_server: Res<AssetServer>, mut load: Load)
             ..default()
         },
         Radius { 0: 15. },
+        CollisionLayers::new(CollisionLayers::PLAYER, CollisionLayers::ALL),
         Verlet::from_translation(player_translation),
         AmbientFriction,
         Gravity,
@@ -318,6 +321,7 @@ pub fn update_cursor_translation(
 /// A point that can be used in terrain lines.
 #[derive(Component, Default, SaveAndLoad)]
 pub struct TerrainPoint {
+    #[save(skip)]
     selected: bool,
 }
 
@@ -335,6 +339,7 @@ impl TerrainPoint {
             commands.spawn((
                 SaveConfig {
                     path: "./map".into(),
+                    ..default()
                 },
                 TerrainPoint::default(),
                 Transform::from_translation(Vec3::new(translation.0.x, translation.0.y, 0.)),
@@ -453,6 +458,7 @@ impl TerrainPoint {
                             .spawn((
                                 SaveConfig {
                                     path: "./map".into(),
+                                    ..default()
                                 },
                                 TerrainLine::new((points_selected[0].0, points_selected[1].0)),
                             ))
@@ -487,8 +493,9 @@ struct BelongsToTerrain(Entity);
 
 //MARK: TerrainLine
 /// Allows only 1 line to be selected at a time.
-#[derive(Resource, Default)]
-struct LineSelected(Option<Entity>);
+#[derive(Resource, Default, SaveAndLoadResource)]
+#[save(path = "./map")]
+struct LineSelected(#[save(map)] Option<Entity>);
 
 impl LineSelected {
     fn ui(
@@ -733,10 +740,13 @@ impl LineSelected {
 /// A bunch of circles that look like terrain hopefully.
 #[derive(Component, SaveAndLoad, Clone)]
 struct TerrainLine {
+    #[save(map)]
     point_1: Entity,
+    #[save(map)]
     point_2: Entity,
 
-    // Should the line (re)generate everything?
+    // Should the line (re)generate everything? Loaded lines do once their path has loaded.
+    #[save(skip)]
     generate: bool,
 
     // The seed that determines how the terrain will randomly generate.
@@ -774,7 +784,8 @@ struct TerrainLine {
     // The colour of the nodules.
     colour: [f32; 3],
 
-    // Whether we want to add colliders to the nodules.
+    // Whether we want the nodules to collide.
+    // The surface collides as capsules joining its nodules, and every nodule below it as its own circle.
     collision: bool,
 }
 
@@ -856,6 +867,20 @@ impl TerrainLine {
         });
     }
 
+    /// Generates the lines when their path has loaded, once all of their points are there too.
+    fn on_load(mut lines: Query<&mut Self>, mut load_finished: EventReader<LoadFinished>) {
+        load_finished
+            .read()
+            .filter(|load_finished| load_finished.succeeded)
+            .for_each(|load_finished| {
+                load_finished.entities.iter().for_each(|entity| {
+                    if let Ok(mut line) = lines.get_mut(*entity) {
+                        line.generate = true;
+                    }
+                });
+            });
+    }
+
     /// Deletes any lines that are missing points.
     fn validate(
         lines: Query<(Entity, &Self)>,
@@ -872,22 +897,6 @@ impl TerrainLine {
         });
     }
 
-    /// Generates the lines when they load.
-    fn on_load(mut load_finish: EventReader<LoadFinish>, mut commands: Commands) {
-        load_finish.read().for_each(|load_finish| {
-            if !load_finish.is_component::<Self>() {
-                return;
-            }
-
-            commands
-                .entity(load_finish.entity)
-                .entry::<Self>()
-                .and_modify(|mut line| {
-                    line.generate = true;
-                });
-        });
-    }
-
     /// Deletes itself properly. Makes sure to delete every generated entity aswell.
     fn delete(
         entity: Entity,
@@ -961,7 +970,7 @@ impl TerrainLine {
         commands: &'a mut Commands,
         asset_server: &AssetServer,
     ) -> EntityCommands<'a> {
-        let mut entity_commands = commands.spawn((
+        commands.spawn((
             Transform::from_translation(Vec3::new(translation.x, translation.y, self.z)),
             Sprite {
                 image: asset_server.load("nodule.png"),
@@ -970,15 +979,22 @@ impl TerrainLine {
                 ..default()
             },
             BelongsToTerrain(entity),
-        ));
-
-        if self.collision {
-            entity_commands.insert(Radius {
-                0: self.diameter / 2.,
-            });
-        }
+        ))
+    }
 
-        entity_commands
+    /// Joins 2 neighbouring surface nodules with a capsule as wide as them.
+    /// Surface nodules collide through these, rather than a circle each.
+    fn create_collider(&self, entity: Entity, from: Vec2, to: Vec2, commands: &mut Commands) {
+        commands.spawn((
+            Transform::from_translation(from.extend(self.z)),
+            Collider::Capsule {
+                start: Vec2::ZERO,
+                end: to - from,
+                radius: self.diameter / 2.,
+            },
+            CollisionLayers::new(CollisionLayers::TERRAIN, CollisionLayers::ALL),
+            BelongsToTerrain(entity),
+        ));
     }
 
     fn line(
@@ -994,6 +1010,7 @@ impl TerrainLine {
         let mut rng = StdRng::seed_from_u64(self.seed);
 
         let mut offset_y = 0.;
+        let mut previous_surface = None;
 
         //let distance = self.previous_translation.distance(to);
         let distance_x = (from.x - to.x).abs();
@@ -1027,7 +1044,24 @@ impl TerrainLine {
                 ) + from
                     + jitter;
 
-                self.create(entity, translation, commands, asset_server);
+                let mut nodule = self.create(entity, translation, commands, asset_server);
+
+                if !self.collision {
+                    continue;
+                }
+
+                if depth > 0 {
+                    nodule.insert((
+                        Radius(self.diameter / 2.),
+                        CollisionLayers::new(CollisionLayers::TERRAIN, CollisionLayers::ALL),
+                    ));
+                    continue;
+                }
+
+                if let Some(previous_surface) = previous_surface {
+                    self.create_collider(entity, previous_surface, translation, commands);
+                }
+                previous_surface = Some(translation);
             }
         }
     }
@@ -1191,6 +1225,16 @@ macro_rules! ok_or_error_and_return {
             }
         }
     };
+
+    ($result:expr, $message:expr, $return:expr) => {
+        match $result {
+            Ok(result) => result,
+            Err(error) => {
+                error!("{} {error}", $message);
+                return $return;
+            }
+        }
+    };
 }
 
 #[derive(Clone)]
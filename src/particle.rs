@@ -59,12 +59,14 @@ impl StepUp {
             &mut Motion,
             &Radius,
             &StepUp,
+            Option<&CollisionLayers>,
             &Ticker,
         )>,
-        colliders: Query<(&Radius, &Transform), Without<StepUp>>,
+        colliders: ColliderQuery<Without<StepUp>>,
     ) {
         particles.par_iter_mut().for_each(
-            |(entity, mut transform, mut motion, collider, step_up, ticker)| {
+            |(entity, mut transform, mut motion, collider, step_up, layers, ticker)| {
+                let layers = layers.copied().unwrap_or_default();
                 ticker.0.run(|| {
                     if !motion.enabled[0] {
                         // Should we take the y motion into account, so we don't accidentally fall through the floor perhaps?
@@ -74,6 +76,7 @@ impl StepUp {
                                 collider.0,
                                 step_up.0,
                                 Some(entity),
+                                layers,
                                 &colliders,
                             );
 
@@ -143,14 +146,21 @@ impl StopOnCollision {
     pub fn motion(
         collider_grid: Res<ColliderGrid>,
         mut particles: Query<
-            (Entity, &Transform, &mut Motion, &Radius, &Ticker),
+            (
+                Entity,
+                &Transform,
+                &mut Motion,
+                &Radius,
+                Option<&CollisionLayers>,
+                &Ticker,
+            ),
             With<StopOnCollision>,
         >,
-        colliders: Query<(&Radius, &Transform)>,
+        colliders: ColliderQuery,
     ) {
-        particles
-            .par_iter_mut()
-            .for_each(|(entity, transform, mut motion, collider, ticker)| {
+        particles.par_iter_mut().for_each(
+            |(entity, transform, mut motion, collider, layers, ticker)| {
+                let layers = layers.copied().unwrap_or_default();
                 ticker.0.run(|| {
                     if motion.amount == Vec2::ZERO {
                         return;
@@ -162,6 +172,7 @@ impl StopOnCollision {
                         transform.translation.xy() + motion.amount,
                         collider.0,
                         Some(entity),
+                        layers,
                         &colliders,
                     );
 
@@ -170,6 +181,7 @@ impl StopOnCollision {
                             transform.translation.xy() + Vec2::new(motion.amount.x, 0.),
                             collider.0,
                             Some(entity),
+                            layers,
                             &colliders,
                         );
 
@@ -177,11 +189,13 @@ impl StopOnCollision {
                             transform.translation.xy() + Vec2::new(0., motion.amount.y),
                             collider.0,
                             Some(entity),
+                            layers,
                             &colliders,
                         );
                     }
                 });
-            });
+            },
+        );
     }
 
     // TODO: Currently we are using a janky hack to get step up working with velocity.
@@ -194,14 +208,16 @@ impl StopOnCollision {
                 &mut Velocity,
                 &Radius,
                 Option<&StepUp>,
+                Option<&CollisionLayers>,
                 &Ticker,
             ),
             With<StopOnCollision>,
         >,
-        colliders: Query<(&Radius, &Transform)>,
+        colliders: ColliderQuery,
     ) {
         particles.par_iter_mut().for_each(
-            |(entity, transform, mut velocity, collider, step_up, ticker)| {
+            |(entity, transform, mut velocity, collider, step_up, layers, ticker)| {
+                let layers = layers.copied().unwrap_or_default();
                 ticker.0.run(|| {
                     if **velocity == Vec2::ZERO {
                         return;
@@ -211,6 +227,7 @@ impl StopOnCollision {
                         transform.translation.xy() + **velocity,
                         collider.0,
                         Some(entity),
+                        layers,
                         &colliders,
                     );
 
@@ -219,6 +236,7 @@ impl StopOnCollision {
                             transform.translation.xy() + Vec2::new(0., velocity.y),
                             collider.0,
                             Some(entity),
+                            layers,
                             &colliders,
                         ) {
                             velocity.y = 0.;
@@ -228,6 +246,7 @@ impl StopOnCollision {
                             transform.translation.xy() + Vec2::new(velocity.x, 0.),
                             collider.0,
                             Some(entity),
+                            layers,
                             &colliders,
                         ) {
                             if let Some(step_up) = step_up {
@@ -304,20 +323,21 @@ impl Verlet {
 
     pub fn collide(
         commands: ParallelCommands,
-        mut particles: Query<(Entity, &mut Verlet, &Transform)>,
+        mut particles: Query<(Entity, &mut Verlet, &Transform, Option<&CollisionLayers>)>,
         //time: Res<Time>,
         collider_grid: Res<ColliderGrid>,
-        colliders: Query<(&Radius, &Transform)>,
+        colliders: ColliderQuery,
     ) {
         particles
             .par_iter_mut()
-            .for_each(|(entity, mut particle, transform)| {
+            .for_each(|(entity, mut particle, transform, layers)| {
                 // TODO: Profile adding an if previous translation equals translation, then perhaps don't check for collisions?
 
                 if collider_grid.collides_with_any(
                     transform.translation.xy(),
                     15.,
                     Some(entity),
+                    layers.copied().unwrap_or_default(),
                     &colliders,
                 ) {
                     info!("!");
@@ -434,16 +454,19 @@ fn distance_constraint(distance_desired: f32, translation: Vec2, target_translat
 
 /// Chains entities between anchor and target.
 /// If target cannot be reached, the chain will still remain anchored.
-#[derive(Component)]
+#[derive(Component, SaveAndLoad)]
 pub struct Chain {
     // Anchor is a seperate entity because we may want multiple chains on one anchor.
+    #[save(map)]
     pub anchor: Entity,
     /// The links of the chain.
     /// In order of closest to anchor to closest to target. Roughly.
     /// The translation is the source of truth, I think. Transform's translation's xy will be set to it.
     /// (distance_to_previous, entity, translation)
     // TODO: How does the last link interact with target?
+    #[save(map)]
     pub links: Vec<(f32, Entity, Vec2)>,
+    #[save(map)]
     pub target: Option<Entity>,
 }
 
@@ -70,8 +70,9 @@ pub fn camera_follow(
     camera_transform.translation.y = new_position.y;
 }
 
-#[derive(Component)]
+#[derive(Component, SaveAndLoad)]
 pub struct AbilityOrb {
+    #[save(map)]
     pub following: Option<Entity>,
     pub distance: f32,
 }
@@ -96,19 +97,19 @@ pub struct AbilityOrb {
 pub fn debug_collisions(
     mut gizmos: Gizmos,
     players: Query<&Transform, With<Player>>,
-    colliders: Query<(&Transform, &Radius)>,
+    colliders: Query<(&Transform, &Collider)>,
     collider_grid: Res<ColliderGrid>,
 ) {
     players.iter().for_each(|transform| {
         info!("{} translation", transform.translation.xy().round());
-        if let Some(index) = collider_grid.translation_to_index(transform.translation.xy()) {
-            info!("{} entities", collider_grid.cells[index].0.len());
-            collider_grid.cells[index].0.iter().for_each(|entity| {
-                let (transform, collider) = colliders.get(*entity).unwrap();
-
-                gizmos.circle_2d(transform.translation.xy(), collider.0, RED);
-            });
-        }
+        let entities =
+            collider_grid.cell(collider_grid.translation_to_cell(transform.translation.xy()));
+        info!("{} entities", entities.len());
+        entities.iter().for_each(|entity| {
+            let (transform, collider) = colliders.get(*entity).unwrap();
+
+            collider.draw(&mut gizmos, transform.translation.xy(), RED);
+        });
     });
 }
 
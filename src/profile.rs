@@ -110,6 +110,7 @@ fn profile_name_load(mut ui: Ui, language: Option<Res<Language>>) {
                 },
                 SaveConfig {
                     path: String::from("./profiles"),
+                    ..default()
                 },
                 ActiveProfile,
             ));
@@ -176,7 +177,7 @@ fn selection_menu_load(
 fn profile_buttons_load(
     profiles: Query<&Profile>,
     root: Option<Single<Entity, With<Root>>>,
-    mut loaded: EventReader<LoadFinish>,
+    mut load_finished: EventReader<LoadFinished>,
     mut menu: MenuReader,
     mut commands: Commands,
     asset_server: Res<AssetServer>,
@@ -185,31 +186,35 @@ fn profile_buttons_load(
         return;
     }
 
-    // This will fail for 1 frame. I don't think that loses us any profiles? I hope.
-    // Keeping the error, to remind myself that this isn't a great solution.
-    some_err!(root);
-    let mut root = commands.entity(*root);
+    // The root is spawned in the same frame as the load starts, so it always exists by the time the load finishes.
+    let root = root.map(|root| *root);
 
-    loaded.read().for_each(|loaded| {
-        assert_return!(loaded.is_component::<Profile>());
+    load_finished.read().for_each(|load_finished| {
+        assert_return!(SavePath::same(&load_finished.path, "./profiles"));
+        some_err!(root);
+        let mut root = commands.entity(root);
 
-        let profile = ok_err!(profiles.get(loaded.entity));
+        load_finished.entities.iter().for_each(|entity| {
+            let Ok(profile) = profiles.get(*entity) else {
+                return;
+            };
 
-        info!("Loaded profile.");
+            info!("Loaded profile.");
 
-        root.with_child((
-            Text::new(&profile.name),
-            TextColor(WHITE.into()),
-            TextFont {
-                font: asset_server.load("fonts/AzeretMono.ttf"),
-                font_size: 100.,
-                ..default()
-            },
-            Button,
-            FromMenu,
-            BackgroundColor(Srgba::gray(0.3).into()),
-            ProfileButton(Some(loaded.entity)),
-        ));
+            root.with_child((
+                Text::new(&profile.name),
+                TextColor(WHITE.into()),
+                TextFont {
+                    font: asset_server.load("fonts/AzeretMono.ttf"),
+                    font_size: 100.,
+                    ..default()
+                },
+                Button,
+                FromMenu,
+                BackgroundColor(Srgba::gray(0.3).into()),
+                ProfileButton(Some(*entity)),
+            ));
+        });
     });
 }
 
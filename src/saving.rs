@@ -1,169 +1,1061 @@
+use std::{
+    collections::BTreeMap,
+    sync::{Mutex, MutexGuard, PoisonError},
+};
+
+use archive::SaveTypes;
+use bevy::{ecs::system::SystemChangeTick, utils::HashSet};
+use incremental::SavedPaths;
+use integrity::LoadIssueReporter;
+use load_options::{ComponentFilter, LoadOptions};
+use migration::Versioned;
+use save_path::InvalidSavePath;
 use serde::de::DeserializeOwned;
+use storage::SaveStore;
+
+pub use resources::{setup_app_for_saving_and_loading_resource, SaveAndLoadResource};
 
 pub use crate::prelude::*;
 
+mod archive;
+mod autosave;
+mod external;
+mod hot_reload;
+mod incremental;
+mod integrity;
+mod load_options;
+mod map_entities;
+mod metadata;
+mod migration;
+mod resources;
+mod save_id;
+mod save_path;
+mod storage;
+#[cfg(test)]
+mod tests;
+
 pub mod prelude {
-    pub use super::{Load, LoadFinish, Save, SaveConfig};
+    pub use super::{
+        archive::prelude::*, autosave::prelude::*, hot_reload::prelude::*, integrity::prelude::*,
+        load_options::prelude::*, map_entities::prelude::*, metadata::prelude::*,
+        migration::prelude::*, save_id::prelude::*, save_path::prelude::*, storage::prelude::*,
+        Load, LoadFinish, LoadFinished, LoadProgress, PathProgress, Save, SaveBackups, SaveConfig,
+        SaveFinished, SaveFormat, SavingPlugin,
+    };
 }
 
-/// Anything saved will be relative to this path.
+/// Anything saved will be relative to this path, inside the SaveStore.
 // Not a path, due to Paths not being allowed as constants.
-const SAVE_PATH: &str = "./assets/saves/";
-/// Same as above, but relative to ./assets instead.
-const SAVE_PATH_RELATIVE_TO_ASSETS: &str = "./saves";
+const SAVE_PATH: &str = "./saves/";
+/// Saves are written here first. Only once every component has been written do they replace the save in SAVE_PATH.
+const STAGING_PATH: &str = "./staging/";
+/// Previous saves are moved here instead of being deleted.
+/// Each save path gets its own folder, containing numbered backups. 0 is the newest.
+const BACKUP_PATH: &str = "./backups/";
+/// The name of the file that SaveFormat::Bundle writes into each save path.
+const BUNDLE_FILE_NAME: &str = "save.bundle.json";
+
+/// Everything saving and loading needs, and the types it saves itself.
+/// Types deriving SaveAndLoad register themselves on top of this.
+pub struct SavingPlugin;
+
+impl Plugin for SavingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SaveStore>()
+            .init_resource::<SaveBackups>()
+            .init_resource::<SaveTransactions>()
+            .init_resource::<SaveBundles>()
+            .init_resource::<SavedPaths>()
+            .init_resource::<LoadIssuePolicy>()
+            .init_resource::<SaveTypes>()
+            .init_resource::<Migrations>()
+            .init_resource::<Playtime>()
+            .init_resource::<HotReload>()
+            .init_resource::<Autosave>()
+            .add_event::<SavePrepare>()
+            .add_event::<SaveFinished>()
+            .add_event::<SaveComponents>()
+            .add_event::<LoadPrepare>()
+            .add_event::<LoadComponents>()
+            .add_event::<LoadFinished>()
+            .add_event::<LoadFinish>()
+            .add_event::<LoadIssues>()
+            .add_event::<ComponentMigrated>()
+            .add_event::<ArchiveFinished>()
+            .add_event::<InvalidSavePath>()
+            .add_event::<Autosaved>()
+            .add_systems(crate::Update_Early, (hot_reload::watch, save_prepare))
+            .add_systems(
+                Update,
+                (
+                    prepare,
+                    hot_reload::reload,
+                    autosave::autosave,
+                    autosave::autosaved,
+                    metadata::count_playtime,
+                ),
+            )
+            .add_systems(PostUpdate, (save_commit, finish_loads));
+
+        setup_app_for_saving_and_loading::<SaveConfig>(app);
+        external::setup(app);
+    }
+}
 
 #[derive(SystemParam)]
-pub struct Save<'w> {
+pub struct Save<'w, 's> {
     writer: EventWriter<'w, SavePrepare>,
+    commands: Commands<'w, 's>,
+    save_store: Res<'w, SaveStore>,
 }
 
-impl Save<'_> {
+impl Save<'_, '_> {
     /// Saves the path relative to SAVE_PATH.
+    /// If the path isn't allowed, InvalidSavePath is sent instead.
     pub fn path(&mut self, path: impl ToString) {
-        self.writer.send(SavePrepare(path.to_string()));
+        let Some(path) = InvalidSavePath::check(path, &mut self.commands) else {
+            return;
+        };
+        self.writer.send(SavePrepare {
+            destination: path.clone(),
+            path,
+            description: String::new(),
+            screenshot: None,
+        });
+    }
+
+    /// Saves the path relative to SAVE_PATH, with a description in its metadata.
+    pub fn path_described(&mut self, path: impl ToString, description: impl ToString) {
+        let Some(path) = InvalidSavePath::check(path, &mut self.commands) else {
+            return;
+        };
+        self.writer.send(SavePrepare {
+            destination: path.clone(),
+            path,
+            description: description.to_string(),
+            screenshot: None,
+        });
+    }
+
+    /// Saves the path relative to SAVE_PATH, with a description and screenshot in its metadata.
+    /// The screenshot is an image the game has already written, relative to the SaveStore's root.
+    pub fn path_with_screenshot(
+        &mut self,
+        path: impl ToString,
+        description: impl ToString,
+        screenshot: impl ToString,
+    ) {
+        let Some(path) = InvalidSavePath::check(path, &mut self.commands) else {
+            return;
+        };
+        self.writer.send(SavePrepare {
+            destination: path.clone(),
+            path,
+            description: description.to_string(),
+            screenshot: Some(screenshot.to_string()),
+        });
+    }
+
+    /// Saves the entities of the path somewhere else, relative to SAVE_PATH.
+    pub fn path_to(&mut self, path: impl ToString, destination: impl ToString) {
+        let (Some(path), Some(destination)) = (
+            InvalidSavePath::check(path, &mut self.commands),
+            InvalidSavePath::check(destination, &mut self.commands),
+        ) else {
+            return;
+        };
+        self.writer.send(SavePrepare {
+            path,
+            destination,
+            description: String::new(),
+            screenshot: None,
+        });
+    }
+
+    /// Packs the save at the path into a single file, so it can be shared. Load it with Load::import.
+    /// The archive is named relative to ./archives/, and is replaced if it exists.
+    /// Only what has been saved is packed, so save first if the path has changed since.
+    /// ArchiveFinished is sent once it is done.
+    pub fn export(&mut self, path: impl ToString, archive: impl ToString) {
+        let (Some(path), Some(archive)) = (
+            InvalidSavePath::check(path, &mut self.commands),
+            InvalidSavePath::check(archive, &mut self.commands),
+        ) else {
+            return;
+        };
+        let result = archive::export(&**self.save_store, &path, &archive);
+        ArchiveFinished::send(&archive, &path, true, result, &mut self.commands);
+    }
+}
+
+/// How many previous saves to keep for each save path.
+/// When a save replaces an older one, the older one becomes backup 0, and the oldest backup is deleted.
+#[derive(Resource)]
+pub struct SaveBackups(pub usize);
+
+impl Default for SaveBackups {
+    fn default() -> Self {
+        Self(3)
+    }
+}
+
+/// Which saves went wrong while being written to their staging folder.
+#[derive(Resource, Default)]
+pub struct SaveTransactions {
+    /// Paths that failed to write at least 1 component. These won't replace the previous save.
+    failed: HashSet<String>,
+}
+
+impl SaveTransactions {
+    /// Stops the save at this path from replacing the previous save.
+    pub fn fail(&mut self, path: &str) {
+        self.failed.insert(path.to_string());
     }
 }
 
 /// Called just before everything should save.
 /// This will make sure that everything is cleared out before sending the Save event.
-/// The string is the path relative to SAVE_PATH for safety.
-#[init]
+/// Both paths are relative to SAVE_PATH, and validated so they can't leave it.
 #[derive(Event)]
-struct SavePrepare(pub String);
+struct SavePrepare {
+    /// The path of the entities to save, as in their SaveConfig.
+    path: SavePath,
+    /// Where to save them. Usually the same as path.
+    destination: SavePath,
+    /// Written into the save's metadata.
+    description: String,
+    /// Written into the save's metadata.
+    screenshot: Option<String>,
+}
 
-/// Clears EntityToIndex and then sends the save event.
-#[system(Update)]
+/// Prepares an empty staging folder, and then sends the save event.
+/// The previous save is not touched until save_commit.
+/// Runs early, so that every component is written before save_commit runs in the same frame.
+#[allow(clippy::too_many_arguments)]
 fn save_prepare(
     mut save_prepare: EventReader<SavePrepare>,
     mut save_components: EventWriter<SaveComponents>,
-    mut serialise_entity: ResMut<SerialiseEntity>,
+    mut saved_paths: ResMut<SavedPaths>,
+    mut save_transactions: ResMut<SaveTransactions>,
+    entities: Query<(
+        Entity,
+        &SaveConfig,
+        Option<&SaveId>,
+        Option<&Name>,
+        Option<&Parent>,
+    )>,
+    children: Query<&Children>,
+    save_store: Res<SaveStore>,
+    mut commands: Commands,
 ) {
     save_prepare.read().for_each(|save_prepare| {
-        serialise_entity.0.clear();
-        serialise_entity.1 = 0;
+        save_transactions.failed.remove(&*save_prepare.destination);
 
-        //TODO: Backup everything first.
+        // If the previous save is still there, then only what changed since then has to be written.
+        let save_exists = ok_or_error_and_return!(
+            save_store.exists(&Path::new(SAVE_PATH).join(&save_prepare.destination)),
+            "Tried to check if a folder existed and got this error:"
+        );
+        let saved_path = saved_paths
+            .0
+            .entry(save_prepare.destination.to_string())
+            .or_default();
+        saved_path.prepare(save_exists);
+        saved_path.description = save_prepare.description.clone();
+        saved_path.screenshot = save_prepare.screenshot.clone();
+        let in_path = entities
+            .iter()
+            .filter(|(_, save_config, ..)| SavePath::same(&save_config.path, &save_prepare.path))
+            .collect::<Vec<_>>();
+        saved_path.assign_save_ids(
+            in_path
+                .iter()
+                .map(|(entity, _, save_id, name, _)| (*entity, *save_id, *name)),
+            &mut commands,
+        );
+        saved_path.serialise_entity.find_child_indices(
+            in_path
+                .iter()
+                .filter_map(|(.., parent)| parent.map(Parent::get)),
+            &children,
+        );
 
-        let path = Path::new(SAVE_PATH).join(&save_prepare.0);
+        let path = Path::new(STAGING_PATH).join(&save_prepare.destination);
 
         let exists = ok_or_error_and_return!(
-            fs::exists(&path),
+            save_store.exists(&path),
             "Tried to check if a folder existed and got this error:"
         );
 
+        // Left over from a save that never finished, so it can't be trusted.
         if exists {
             ok_or_error_and_return!(
-                fs::remove_dir_all(&path),
+                save_store.remove_dir_all(&path),
                 "Tried to remove a folder and got this error:"
             );
         }
 
         ok_or_error_and_return!(
-            fs::create_dir(&path),
+            save_store.create_dir_all(&path),
             "Tried to create a folder and got this error:"
         );
 
-        save_components.send(SaveComponents(save_prepare.0.clone()));
+        save_components.send(SaveComponents {
+            path: save_prepare.path.to_string(),
+            destination: save_prepare.destination.to_string(),
+        });
+    });
+}
+
+/// Runs after every component has been written to the staging folder.
+/// Moves the previous save into the backups, and then moves the staging folder into its place.
+/// An incremental save only wrote what changed, so the rest of the previous save is copied into the staging folder first.
+#[allow(clippy::too_many_arguments)]
+fn save_commit(
+    mut save_components: EventReader<SaveComponents>,
+    mut save_transactions: ResMut<SaveTransactions>,
+    mut save_bundles: ResMut<SaveBundles>,
+    mut saved_paths: ResMut<SavedPaths>,
+    save_backups: Res<SaveBackups>,
+    save_store: Res<SaveStore>,
+    playtime: Res<Playtime>,
+    mut save_finished: EventWriter<SaveFinished>,
+) {
+    save_components.read().for_each(|save_components| {
+        let destination = &save_components.destination;
+        let staging_path = Path::new(STAGING_PATH).join(destination);
+
+        // Every component has been added to the bundle by now, so it can be written in one go.
+        if let Some(save_bundle) = save_bundles.0.remove(destination) {
+            if !save_bundle.write(&**save_store, &staging_path) {
+                save_transactions.fail(destination);
+            }
+        }
+
+        let saved_path = saved_paths.0.entry(destination.clone()).or_default();
+
+        // Every type has been saved by now, so the metadata can count them.
+        if !saved_path
+            .metadata(&save_components.path, playtime.0)
+            .write(&**save_store, &staging_path)
+        {
+            save_transactions.fail(destination);
+        }
+
+        let succeeded = if save_transactions.failed.remove(destination) {
+            error!("Failed to save {destination}. The previous save has been kept.");
+            saved_path.forget();
+            if let Err(error) = save_store.remove_dir_all(&staging_path) {
+                error!("Tried to remove a failed save and got this error: {error}");
+            }
+            false
+        } else {
+            let committed = if saved_path.incremental {
+                saved_path.copy_unchanged(&**save_store, destination)
+            } else {
+                Ok(())
+            }
+            .and_then(|()| {
+                let count = if autosave::is_slot(destination) {
+                    0
+                } else {
+                    save_backups.0
+                };
+                SaveBackups::commit(&**save_store, destination, &staging_path, count)
+            });
+
+            match committed {
+                Ok(()) => {
+                    saved_path.on_disk = true;
+                    true
+                }
+                Err(error) => {
+                    error!(
+                        "Tried to move the staged save {destination} into place and got this \
+                         error: {error}\nThe next save will write everything."
+                    );
+                    saved_path.forget();
+                    false
+                }
+            }
+        };
+
+        save_finished.send(SaveFinished {
+            path: save_components.path.clone(),
+            destination: destination.clone(),
+            succeeded,
+        });
     });
 }
 
+/// Sent once a save has been flushed to disk, or has failed to be.
+#[derive(Event, Debug)]
+pub struct SaveFinished {
+    /// The path of the entities that were saved.
+    pub path: String,
+    /// Where they were saved to. Usually the same as path.
+    pub destination: String,
+    pub succeeded: bool,
+}
+
+impl SaveBackups {
+    /// Moves the previous save into the backups, and then moves the staging folder into its place.
+    fn commit(
+        save_store: &dyn SaveStorage,
+        path: &str,
+        staging_path: &Path,
+        count: usize,
+    ) -> std::io::Result<()> {
+        let save_path = Path::new(SAVE_PATH).join(path);
+
+        if save_store.exists(&save_path)? {
+            Self::rotate(save_store, path, count)?;
+        } else if let Some(parent) = save_path.parent() {
+            save_store.create_dir_all(parent)?;
+        }
+
+        // Between the 2 renames there is no save, only backup 0. If the second one fails, put the old save back.
+        // If the game stops in between instead, the next load recovers it.
+        save_store
+            .rename(staging_path, &save_path)
+            .inspect_err(|_| {
+                if let Err(error) = Self::recover(save_store, path) {
+                    error!("Tried to recover {path} from its backup and got this error: {error}");
+                }
+            })
+    }
+
+    /// Moves backup 0 back into place if the save itself is missing, such as when a commit was interrupted.
+    /// Returns whether it did. The backups after it are left alone, so there is a gap where 0 was.
+    fn recover(save_store: &dyn SaveStorage, path: &str) -> std::io::Result<bool> {
+        let save_path = Path::new(SAVE_PATH).join(path);
+        let backup_path = Path::new(BACKUP_PATH).join(path).join("0");
+
+        if save_store.exists(&save_path)? || !save_store.exists(&backup_path)? {
+            return Ok(false);
+        }
+
+        warn!("The save {path} is missing, so its newest backup is being put back.");
+        save_store.rename(&backup_path, &save_path)?;
+        Ok(true)
+    }
+
+    /// Moves the save at the path into backup 0, shifting every other backup along by 1.
+    /// Anything that would go past the backup count is deleted.
+    fn rotate(save_store: &dyn SaveStorage, path: &str, count: usize) -> std::io::Result<()> {
+        let save_path = Path::new(SAVE_PATH).join(path);
+
+        if count == 0 {
+            return save_store.remove_dir_all(&save_path);
+        }
+
+        let backups_path = Path::new(BACKUP_PATH).join(path);
+        save_store.create_dir_all(&backups_path)?;
+
+        // Oldest first, so there is always an empty space to move into.
+        for index in (0..count).rev() {
+            let backup_path = backups_path.join(index.to_string());
+
+            if !save_store.exists(&backup_path)? {
+                continue;
+            }
+
+            if index + 1 == count {
+                save_store.remove_dir_all(&backup_path)?;
+            } else {
+                save_store.rename(&backup_path, &backups_path.join((index + 1).to_string()))?;
+            }
+        }
+
+        save_store.rename(&save_path, &backups_path.join("0"))
+    }
+
+    /// Gets the index of the newest backup that can be loaded.
+    /// Backups are only ever created from complete saves, so any that exist and contain entities are good.
+    fn newest(save_store: &dyn SaveStorage, path: &str, count: usize) -> Option<usize> {
+        let backups_path = Path::new(BACKUP_PATH).join(path);
+
+        (0..count).find(|index| {
+            save_store
+                .read_dir(&backups_path.join(index.to_string()))
+                .is_ok_and(|entities| !entities.is_empty())
+        })
+    }
+}
+
 /// An event that is called whenever all components on entities with a matching saveconfig's path should save.
-#[init]
 #[derive(Event)]
-pub struct SaveComponents(pub String);
+pub struct SaveComponents {
+    /// The path of the entities to save, as in their SaveConfig.
+    pub path: String,
+    /// Where to save them, relative to SAVE_PATH.
+    pub destination: String,
+}
 
 impl SaveComponents {
+    /// Writes the value into the staging folder.
+    /// Returns false if this failed, in which case the save should not replace the previous one.
     fn to_serialised_entity<T: Serialize>(
+        save_store: &dyn SaveStorage,
         value: &T,
-        serialised_entity: SerialisedEntity,
+        folder_name: &str,
         path: impl AsRef<Path>,
         file_name: &str,
-    ) {
-        let folder_path = Path::new(SAVE_PATH)
+    ) -> bool {
+        let file_path = Path::new(STAGING_PATH)
             .join(path)
-            .join(format!("./{}", serialised_entity.0));
-
-        let exists = ok_or_error_and_return!(
-            fs::exists(&folder_path),
-            "Tried to check if a folder existed and got this error:"
-        );
-        if !exists {
-            ok_or_error_and_return!(
-                fs::create_dir(&folder_path),
-                "Tried to create a folder. Got this error:"
-            );
-        }
-
-        let file_path = folder_path.join(format!("./component.{}.json", file_name));
+            .join(folder_name)
+            .join(format!("component.{}.json", file_name));
 
-        let exists = ok_or_error_and_return!(
-            fs::exists(&file_path),
-            "Tried to check if a file existed and got this error:"
-        );
-        if exists {
-            ok_or_error_and_return!(
-                fs::remove_file(&file_path),
-                "Tried to remove a file. Got error:"
-            );
-        }
-
-        let file = ok_or_error_and_return!(
-            File::create(&file_path),
-            "Tried to create a file. Got this error:"
+        let serialised = ok_or_error_and_return!(
+            serde_json::to_vec_pretty(value),
+            "Tried to save a file. During serialisation got this error:",
+            false
         );
         ok_or_error_and_return!(
-            serde_json::to_writer_pretty(file, value),
-            "Tried to save a file. During serialisation got this error:"
+            save_store.write(&file_path, &serialised),
+            "Tried to write a file. Got this error:",
+            false
         );
+
+        true
     }
 }
 
 #[derive(SystemParam)]
-pub struct Load<'w> {
+pub struct Load<'w, 's> {
     writer: EventWriter<'w, LoadPrepare>,
+    commands: Commands<'w, 's>,
+    save_store: Res<'w, SaveStore>,
+    save_backups: Res<'w, SaveBackups>,
+    save_types: Res<'w, SaveTypes>,
+    migrations: Res<'w, Migrations>,
 }
 
-impl Load<'_> {
+impl Load<'_, '_> {
     /// Loads the path relative to SAVE_PATH.
+    /// If the path isn't allowed, InvalidSavePath is sent instead.
     pub fn path(&mut self, path: impl ToString) {
-        self.writer.send(LoadPrepare(path.to_string()));
+        self.path_with(path, LoadOptions::default());
+    }
+
+    /// Loads the path relative to SAVE_PATH, changing how with options.
+    pub fn path_with(&mut self, path: impl ToString, options: LoadOptions) {
+        let Some(path) = InvalidSavePath::check(path, &mut self.commands) else {
+            return;
+        };
+        self.writer.send(LoadPrepare {
+            folder: Path::new(SAVE_PATH).join(&path),
+            path,
+            from_save: true,
+            options,
+        });
+    }
+
+    /// Loads the newest good backup of the path relative to SAVE_PATH.
+    /// Useful for when the save itself is missing or broken.
+    pub fn backup(&mut self, path: impl ToString) {
+        let Some(path) = InvalidSavePath::check(path, &mut self.commands) else {
+            return;
+        };
+        let Some(index) = SaveBackups::newest(&**self.save_store, &path, self.save_backups.0)
+        else {
+            error!("Tried to load a backup of {path}, but none exist.");
+            return;
+        };
+
+        info!("Loading backup {index} of {path}.");
+
+        self.writer.send(LoadPrepare {
+            folder: Path::new(BACKUP_PATH).join(&path).join(index.to_string()),
+            path,
+            from_save: false,
+            options: LoadOptions::default(),
+        });
+    }
+
+    /// Unpacks an archive made by Save::export into the path relative to SAVE_PATH, and then loads it.
+    /// The path must not have a save yet. Nothing is unpacked unless every file in the archive can be loaded.
+    /// The entities belong to the path they are unpacked into, rather than the one they were exported from.
+    /// ArchiveFinished is sent once it has been unpacked, or has failed to be.
+    pub fn import(&mut self, archive: impl ToString, path: impl ToString) {
+        let (Some(archive), Some(path)) = (
+            InvalidSavePath::check(archive, &mut self.commands),
+            InvalidSavePath::check(path, &mut self.commands),
+        ) else {
+            return;
+        };
+
+        let result = archive::import(
+            &**self.save_store,
+            &self.save_types,
+            &self.migrations,
+            &archive,
+            &path,
+        );
+        let imported = result.is_ok();
+        ArchiveFinished::send(&archive, &path, false, result, &mut self.commands);
+
+        if imported {
+            self.writer.send(LoadPrepare {
+                folder: Path::new(SAVE_PATH).join(&path),
+                path,
+                from_save: true,
+                options: LoadOptions::default(),
+            });
+        }
+    }
+
+    /// Loads an autosave slot of the path relative to SAVE_PATH.
+    pub fn autosave(&mut self, path: impl ToString, slot: usize) {
+        let Some(path) = InvalidSavePath::check(path, &mut self.commands) else {
+            return;
+        };
+        self.writer.send(LoadPrepare {
+            folder: Path::new(SAVE_PATH).join(autosave::destination(&path, slot)),
+            path,
+            from_save: false,
+            options: LoadOptions::default(),
+        });
     }
 }
 
 /// Indicates to start loading from that path relative to SAVE_PATH.
-#[init]
+/// Whatever folder it is loaded from, the entities still belong to the path.
 #[derive(Event)]
-struct LoadPrepare(pub String);
+struct LoadPrepare {
+    path: SavePath,
+    /// The folder to actually load from, inside the SaveStore.
+    /// This is usually the save itself, but may be a backup or autosave.
+    folder: PathBuf,
+    /// Whether it is loaded from the save itself, rather than a backup or autosave.
+    from_save: bool,
+    options: LoadOptions,
+}
 
-#[system(Update)]
 fn prepare(
     mut load_prepare: EventReader<LoadPrepare>,
     mut load_components: EventWriter<LoadComponents>,
-    mut deserialise_entity: ResMut<DeserialiseEntity>,
-    loaded_entities: Query<(Entity, &SaveConfig)>,
-    mut commands: Commands,
+    mut load_progress: ResMut<LoadProgress>,
+    save_store: Res<SaveStore>,
+    asset_server: Option<Res<AssetServer>>,
+    loaded_entities: Query<(Entity, &SaveConfig, Option<&SaveId>)>,
 ) {
     load_prepare.read().for_each(|load_prepare| {
-        // Deload any entities in the path we want to load.
-        loaded_entities.iter().for_each(|(entity, save_config)| {
-            if save_config.path == load_prepare.0 {
-                commands.entity(entity).despawn();
-            }
-        });
+        // Its entities would be mixed up with those of the load that is already going.
+        if load_progress.paths.contains_key(&*load_prepare.path) {
+            warn!(
+                "Tried to load {} while it was still loading, so it was skipped.",
+                load_prepare.path
+            );
+            return;
+        }
+
+        // A save that was interrupted between its 2 renames only exists as backup 0.
+        if load_prepare.from_save {
+            ok_or_error_and_return!(
+                SaveBackups::recover(&**save_store, &load_prepare.path),
+                format!(
+                    "Tried to recover {} from its backup. Got this error:",
+                    load_prepare.path
+                )
+            );
+        }
 
-        // Clear this, to assure us that no nonsense shall occur. There is more than 1 save path, so this is required.
-        deserialise_entity.0.clear();
+        // Everything is read up front, so each type only has to pick out its own components.
+        let save_files = ok_or_error_and_return!(
+            SaveFiles::read(&**save_store, &load_prepare.folder),
+            format!(
+                "Tried to load {} from {}. Got this error:",
+                load_prepare.path,
+                load_prepare.folder.display()
+            )
+        );
+
+        let in_path = loaded_entities
+            .iter()
+            .filter(|(_, save_config, _)| SavePath::same(&save_config.path, &load_prepare.path));
+
+        // Each load gets its own, so that paths loading at the same time don't mix up their entities.
+        let mut deserialise_entity = DeserialiseEntity::new(asset_server.as_deref().cloned());
+        deserialise_entity.folder_names = save_files.folder_names.clone();
+
+        // The entities already in the path are replaced, unless they are being added to.
+        // They are only despawned once the load has succeeded, so a failed load leaves them as they were.
+        let replaced = if load_prepare.options.additive {
+            // They keep their SaveIds, so loaded entities with the same ones are given others.
+            in_path
+                .filter_map(|(_, _, save_id)| save_id)
+                .for_each(|save_id| {
+                    deserialise_entity.unused_save_id(save_id.0);
+                });
+            vec![]
+        } else {
+            in_path.map(|(entity, ..)| entity).collect()
+        };
+        let deserialise_entity = Arc::new(Mutex::new(deserialise_entity));
+
+        // Every type loads on its own, so the load is only finished once all of them are.
+        let types = load_progress.types;
+        load_progress.paths.insert(
+            load_prepare.path.to_string(),
+            PathProgress {
+                remaining: types,
+                types,
+                components: 0,
+                from_save: load_prepare.from_save,
+                options: load_prepare.options.clone(),
+                deserialise_entity: deserialise_entity.clone(),
+                replaced,
+            },
+        );
 
-        load_components.send(LoadComponents(load_prepare.0.clone()));
+        load_components.send(LoadComponents {
+            path: load_prepare.path.to_string(),
+            files: Arc::new(save_files),
+            filter: load_prepare.options.filter.clone(),
+            deserialise_entity,
+        });
     });
 }
 
-/// Loads components from entities in the path that is the stored string.
-#[init]
+/// Loads components from entities in the path.
 #[derive(Event)]
-pub struct LoadComponents(pub String);
+pub struct LoadComponents {
+    /// The path relative to SAVE_PATH.
+    pub path: String,
+    /// Everything that was read from the folder being loaded.
+    pub files: Arc<SaveFiles>,
+    /// Types that aren't allowed by this skip the path.
+    pub filter: ComponentFilter,
+    /// Shared by every type loading this path, and by nothing else.
+    pub deserialise_entity: Arc<Mutex<DeserialiseEntity>>,
+}
+
+impl LoadComponents {
+    pub fn deserialise_entity(&self) -> MutexGuard<'_, DeserialiseEntity> {
+        // Every type loading the path takes its turn with it. A panic while it is locked takes the app down too, so poisoning doesn't matter.
+        self.deserialise_entity
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Every file of a save, read but not yet migrated or deserialised.
+/// Deserialising is left to each type, as only then can the migrations be accessed.
+#[derive(Default)]
+pub struct SaveFiles {
+    /// The components of each type, keyed by their STRUCT_IDENT_LOWERCASE.
+    components: HashMap<String, Vec<ComponentFile>>,
+    /// The folder of each entity, keyed by its SaveId.
+    folder_names: HashMap<u32, String>,
+    /// The contents of each resource's file, keyed by its STRUCT_IDENT_LOWERCASE.
+    resources: HashMap<String, Vec<u8>>,
+}
+
+/// A component's file, read as json.
+struct ComponentFile {
+    entity: SerialisedEntity,
+    /// Where it was read from. Bundled components share the bundle's file.
+    file: PathBuf,
+    value: serde_json::Value,
+}
+
+impl SaveFiles {
+    /// Reads every entity folder, bundle and resource in the folder.
+    /// A file that can't be parsed is logged and skipped, but failing to read the folder fails the load.
+    fn read(save_store: &dyn SaveStorage, folder: &Path) -> std::io::Result<Self> {
+        let mut save_files = Self::default();
+
+        for entry in save_store.read_dir(folder)? {
+            let path = folder.join(&entry.name);
+
+            if entry.is_folder {
+                let Some(save_id) = save_id::parse_folder_name(&entry.name) else {
+                    error!(
+                        "An entity's folder name did not end in its SaveId: {}",
+                        path.display()
+                    );
+                    continue;
+                };
+                save_files.folder_names.insert(save_id, entry.name.clone());
+
+                for file in save_store.read_dir(&path)? {
+                    save_files.read_component(
+                        save_store,
+                        SerialisedEntity(save_id),
+                        path.join(&file.name),
+                    );
+                }
+            } else if entry.name == BUNDLE_FILE_NAME {
+                save_files.read_bundle(save_store, path, |_| true);
+            } else if let Some(resource) = entry.name.strip_suffix(resources::FILE_SUFFIX) {
+                save_files
+                    .resources
+                    .insert(resource.to_string(), save_store.read(&path)?);
+            }
+        }
+
+        Ok(save_files)
+    }
+
+    /// Reads the components of every entity in the bundle that matches.
+    fn read_bundle(
+        &mut self,
+        save_store: &dyn SaveStorage,
+        file: PathBuf,
+        matches: impl Fn(SerialisedEntity) -> bool,
+    ) {
+        let Some(save_bundle) = read_json::<SaveBundle>(save_store, &file) else {
+            return;
+        };
+
+        save_bundle
+            .0
+            .into_iter()
+            .filter(|(entity, _)| matches(*entity))
+            .for_each(|(entity, components)| {
+                components.into_iter().for_each(|(component, value)| {
+                    self.components
+                        .entry(component)
+                        .or_default()
+                        .push(ComponentFile {
+                            entity,
+                            file: file.clone(),
+                            value,
+                        });
+                });
+            });
+    }
+
+    /// Reads a file from an entity's folder. Files that aren't components are ignored.
+    fn read_component(
+        &mut self,
+        save_store: &dyn SaveStorage,
+        entity: SerialisedEntity,
+        file: PathBuf,
+    ) {
+        let Some(component) = file
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(component_name)
+        else {
+            return;
+        };
+        let Some(value) = read_json(save_store, &file) else {
+            return;
+        };
+
+        self.components
+            .entry(component.to_string())
+            .or_default()
+            .push(ComponentFile {
+                entity,
+                file,
+                value,
+            });
+    }
+}
+
+/// The STRUCT_IDENT_LOWERCASE of the component that a file in an entity's folder is for.
+fn component_name(file_name: &str) -> Option<&str> {
+    file_name
+        .strip_prefix("component.")
+        .and_then(|name| name.strip_suffix(".json"))
+}
+
+/// Reads and parses a json file. Any error is logged.
+fn read_json<T: DeserializeOwned>(save_store: &dyn SaveStorage, file: &Path) -> Option<T> {
+    let bytes = ok_or_error_and_return!(
+        save_store.read(file),
+        format!("Tried to read {}. Got this error:", file.display()),
+        None
+    );
+    let value = ok_or_error_and_return!(
+        serde_json::from_slice(&bytes),
+        format!(
+            "Tried to load {}. During deserialisation got this error:",
+            file.display()
+        ),
+        None
+    );
+
+    Some(value)
+}
+
+/// How far along each save path that is still loading is. A loading screen can poll this.
+#[derive(Resource, Default)]
+pub struct LoadProgress {
+    /// How many types have been set up for saving and loading.
+    types: usize,
+    paths: HashMap<String, PathProgress>,
+}
+
+pub struct PathProgress {
+    /// How many SaveAndLoad types haven't finished loading the path yet.
+    remaining: usize,
+    types: usize,
+    components: usize,
+    /// Whether it was loaded from the save itself, rather than a backup or autosave.
+    from_save: bool,
+    options: LoadOptions,
+    /// The same one that was sent with LoadComponents, so the finished load can be checked.
+    deserialise_entity: Arc<Mutex<DeserialiseEntity>>,
+    /// What was in the path before, to be despawned once the load has succeeded.
+    replaced: Vec<Entity>,
+}
+
+impl PathProgress {
+    /// Roughly how much of the path has loaded, from 0 to 1. Each type counts equally.
+    pub fn fraction(&self) -> f32 {
+        if self.types == 0 {
+            return 1.;
+        }
+        1. - self.remaining as f32 / self.types as f32
+    }
+
+    /// How many components have been loaded so far.
+    pub fn components(&self) -> usize {
+        self.components
+    }
+}
+
+impl LoadProgress {
+    /// Whether any path is still loading.
+    pub fn is_loading(&self) -> bool {
+        !self.paths.is_empty()
+    }
+
+    /// The progress of the path, if it is still loading.
+    pub fn path(&self, path: &str) -> Option<&PathProgress> {
+        self.paths.get(&*SavePath::new(path).ok()?)
+    }
+
+    /// Every path that is still loading.
+    pub fn paths(&self) -> impl Iterator<Item = (&str, &PathProgress)> {
+        self.paths
+            .iter()
+            .map(|(path, path_progress)| (path.as_str(), path_progress))
+    }
+
+    fn loaded_component(&mut self, path: &str) {
+        if let Some(path_progress) = self.paths.get_mut(path) {
+            path_progress.components += 1;
+        }
+    }
+
+    /// Called by each SaveAndLoad type once it has loaded everything it can from the path.
+    fn finish_type(&mut self, path: &str) {
+        if let Some(path_progress) = self.paths.get_mut(path) {
+            path_progress.remaining = path_progress.remaining.saturating_sub(1);
+        }
+    }
+
+    /// Removes and returns every path that every type has finished loading.
+    fn take_finished(&mut self) -> Vec<(String, PathProgress)> {
+        let finished = self
+            .paths
+            .iter()
+            .filter(|(_, path_progress)| path_progress.remaining == 0)
+            .map(|(path, _)| path.clone())
+            .collect::<Vec<_>>();
+
+        finished
+            .into_iter()
+            .filter_map(|path| self.paths.remove_entry(&path))
+            .collect()
+    }
+}
+
+/// Runs once every type has finished loading a path.
+/// Checks for dangling references, remembers what was loaded so the next save can be incremental, and sends LoadFinished.
+fn finish_loads(
+    mut load_progress: ResMut<LoadProgress>,
+    mut saved_paths: ResMut<SavedPaths>,
+    mut load_issue_reporter: LoadIssueReporter,
+    mut load_finished: EventWriter<LoadFinished>,
+    system_change_tick: SystemChangeTick,
+    mut commands: Commands,
+) {
+    load_progress
+        .take_finished()
+        .into_iter()
+        .for_each(|(path, path_progress)| {
+            let mut deserialise_entity = path_progress
+                .deserialise_entity
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            // Before the check, so that a missing parent is treated like any other missing entity.
+            deserialise_entity.build_hierarchy(&mut commands);
+            let succeeded =
+                load_issue_reporter.check_integrity(&path, &mut deserialise_entity, &mut commands);
+
+            if succeeded {
+                deserialise_entity.insert_resources(&mut commands);
+                path_progress
+                    .options
+                    .apply(&deserialise_entity, &mut commands);
+                path_progress.replaced.iter().for_each(|entity| {
+                    commands.entity(*entity).despawn();
+                });
+            }
+
+            // Entities that only exist because they were referenced aren't counted.
+            let entities = deserialise_entity
+                .components
+                .values()
+                .flatten()
+                .collect::<HashSet<_>>()
+                .into_iter()
+                .filter_map(|index| deserialise_entity.entities.get(index).copied())
+                .collect::<Vec<_>>();
+            let components = deserialise_entity
+                .components
+                .values()
+                .map(HashSet::len)
+                .sum();
+
+            load_finished.send(LoadFinished {
+                path: path.clone(),
+                entities,
+                components,
+                succeeded,
+            });
+
+            // What was in the path before is still there, and so is what is known about it.
+            if !succeeded {
+                return;
+            }
+
+            // What is in the world no longer matches the save, so the next save must write everything.
+            if !path_progress.options.is_exact() {
+                saved_paths.0.entry(path).or_default().forget();
+                return;
+            }
 
-#[init]
+            saved_paths.loaded(
+                path,
+                path_progress.from_save,
+                &deserialise_entity,
+                system_change_tick.this_run(),
+            );
+        });
+}
+
+/// Sent once every component of a path has been loaded and inserted.
+#[derive(Event, Debug)]
+pub struct LoadFinished {
+    pub path: String,
+    /// Every entity that was given components.
+    pub entities: Vec<Entity>,
+    /// How many components were loaded.
+    pub components: usize,
+    /// False if the LoadIssuePolicy failed the load, in which case everything loaded has been despawned,
+    /// and whatever was in the path before has been kept.
+    pub succeeded: bool,
+}
+
+/// Sent once per component, as soon as it has been loaded. The component will be inserted once commands are applied.
 #[derive(Event, Debug)]
 pub struct LoadFinish {
     pub entity: Entity,
@@ -177,32 +1069,98 @@ impl LoadFinish {
 }
 
 /// Because Entity is opaque, we must convert it to something that will never change.
-#[init]
-#[derive(Resource, Default)]
-pub struct SerialiseEntity(HashMap<Entity, u32>, u32);
+/// Each save path keeps its own, built from the SaveIds of its entities.
+#[derive(Default)]
+pub struct SerialiseEntity {
+    indices: HashMap<Entity, u32>,
+    next_index: u32,
+    /// Where each child in the save path is among its parent's Children. Found again before each save.
+    child_indices: HashMap<Entity, usize>,
+    /// The entity whose component is being serialised.
+    serialising: Option<Entity>,
+}
 
 impl SerialiseEntity {
     pub fn convert(&mut self, entity: Entity) -> SerialisedEntity {
         // Get the index if it exists, else create the index and return it.
-        if let Some(index) = self.0.get(&entity) {
+        if let Some(index) = self.indices.get(&entity) {
             SerialisedEntity(*index)
         } else {
-            let index = self.1;
-            self.1 += 1;
-            self.0.insert(entity, index);
+            let index = self.next_index;
+            self.next_index += 1;
+            self.indices.insert(entity, index);
             SerialisedEntity(index)
         }
     }
+
+    /// Where the entity being serialised is among its parent's Children.
+    pub fn child_index(&self) -> Option<usize> {
+        self.child_indices.get(&self.serialising?).copied()
+    }
+
+    /// Remembers where each child of the parents is among their Children.
+    fn find_child_indices(
+        &mut self,
+        parents: impl Iterator<Item = Entity>,
+        children: &Query<&Children>,
+    ) {
+        self.child_indices.clear();
+        parents
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .filter_map(|parent| children.get(parent).ok())
+            .for_each(|children| {
+                children.iter().enumerate().for_each(|(index, child)| {
+                    self.child_indices.insert(*child, index);
+                });
+            });
+    }
+}
+
+/// What holds a reference being loaded.
+#[derive(Clone, Copy, Debug)]
+enum Referrer {
+    Entity(SerialisedEntity),
+    Resource,
 }
 
 /// The inverse of the previous.
 /// Converts indices to entities.
-// TODO: Does this need to be reset on load? Do we need to despawn all previously loaded entities? Yes. Why?
-#[init]
-#[derive(Resource, Default)]
-pub struct DeserialiseEntity(HashMap<u32, Entity>);
+/// Also remembers which indices were given components, and which were only referenced, so that dangling references can be found.
+/// Each load has its own, as the indices are only unique within a save path.
+#[derive(Default)]
+pub struct DeserialiseEntity {
+    entities: HashMap<u32, Entity>,
+    /// The indices that had each type loaded onto them.
+    components: HashMap<TypeId, HashSet<u32>>,
+    /// The indices whose component of each type was saved with an older version, and had to be migrated.
+    migrated: HashMap<TypeId, HashSet<u32>>,
+    /// The entity and component, or resource, currently being deserialised. Any conversions are references from it.
+    referrer: Option<(Referrer, &'static str)>,
+    /// (referrer, component or resource, referenced)
+    references: Vec<(Referrer, &'static str, SerialisedEntity)>,
+    /// Loaded resources are inserted once the path has loaded, unless the LoadIssuePolicy keeps them out.
+    resources: Vec<(&'static str, Box<dyn FnOnce(&mut Commands) + Send>)>,
+    /// Parent can't be inserted, so the hierarchy is built once the path has loaded. (parent, index, child)
+    children: Vec<(SerialisedEntity, usize, SerialisedEntity)>,
+    /// The folder each entity was loaded from, so that saves keep using it.
+    folder_names: HashMap<u32, String>,
+    /// The SaveIds taken in the path, including those of entities an additive load is adding to.
+    save_ids: HashSet<u32>,
+    /// Higher than every SaveId in save_ids.
+    next_save_id: u32,
+    /// Loads the assets that handles point to. None if there is no AssetServer, such as when running headless.
+    asset_server: Option<AssetServer>,
+}
 
 impl DeserialiseEntity {
+    fn new(asset_server: Option<AssetServer>) -> Self {
+        Self {
+            asset_server,
+            ..default()
+        }
+    }
+
     // Infallible, because we create the entity if it doesn't exist.
     // Anything that needs to create an entity from an index must use this function.
     pub fn convert(
@@ -210,208 +1168,347 @@ impl DeserialiseEntity {
         serialised_entity: SerialisedEntity,
         commands: &mut Commands,
     ) -> Entity {
+        if let Some((referrer, component)) = self.referrer {
+            self.references
+                .push((referrer, component, serialised_entity));
+        }
+
         // Get the index if it exists, else create the index and return it.
-        if let Some(entity) = self.0.get(&serialised_entity.0) {
+        if let Some(entity) = self.entities.get(&serialised_entity.0) {
             *entity
         } else {
-            let entity = commands.spawn_empty().id();
-            self.0.insert(serialised_entity.0, entity);
+            let save_id = self.unused_save_id(serialised_entity.0);
+            let entity = commands.spawn(SaveId(save_id)).id();
+            self.entities.insert(serialised_entity.0, entity);
             entity
         }
     }
+
+    /// Inserts the loaded resources that weren't kept out by the LoadIssuePolicy.
+    fn insert_resources(&mut self, commands: &mut Commands) {
+        self.resources
+            .drain(..)
+            .for_each(|(_, insert)| insert(commands));
+    }
+
+    /// Puts each loaded child into its parent's Children, in the order they were saved in.
+    fn build_hierarchy(&self, commands: &mut Commands) {
+        let mut children = HashMap::<Entity, Vec<(usize, Entity)>>::default();
+        self.children.iter().for_each(|(parent, index, child)| {
+            let (Some(parent), Some(child)) =
+                (self.entities.get(&parent.0), self.entities.get(&child.0))
+            else {
+                return;
+            };
+            children.entry(*parent).or_default().push((*index, *child));
+        });
+
+        children.into_iter().for_each(|(parent, mut children)| {
+            children.sort_by_key(|(index, _)| *index);
+            let children = children
+                .into_iter()
+                .map(|(_, child)| child)
+                .collect::<Vec<_>>();
+            commands.entity(parent).insert_children(0, &children);
+        });
+    }
+
+    /// Takes the SaveId, or an unused one if it is already taken.
+    fn unused_save_id(&mut self, save_id: u32) -> u32 {
+        let save_id = if self.save_ids.contains(&save_id) {
+            self.next_save_id
+        } else {
+            save_id
+        };
+        self.save_ids.insert(save_id);
+        self.next_save_id = self.next_save_id.max(save_id + 1);
+        save_id
+    }
 }
 
 /// Entity is opaque and ethereal. We as such serialise and deserialise from u32.
-/// Each entity gets assigned a number starting from 0 and ascending. If you want to reference that entity, you use that number.
-#[derive(Serialize, Deserialize, Clone, Copy)]
+/// Entities in the save path use their SaveId. Any other entity that is referenced is given the next unused number.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
 pub struct SerialisedEntity(pub u32);
 
 /// We still have decentralised saving, but for per entity information, it can be gotten from here.
 /// This may include:
 /// - path to save at
+/// - format to save in
 /// - TODO: Any more?
-// TODO: Don't derive SaveAndLoad, instead manually implement it.
-// The manual implementation shouldn't create any files. It knows that any entity loaded will have the component.
-#[derive(Component, SaveAndLoad)]
+// TODO: The implementation of SaveAndLoad shouldn't create any files. It knows that any entity loaded will have the component.
+#[derive(Component, Default)]
 pub struct SaveConfig {
     pub path: String,
+    pub format: SaveFormat,
+}
+
+/// How the components of an entity are written to disk.
+#[derive(Serialize, Deserialize, Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SaveFormat {
+    /// A folder per entity, containing a file per component. Easy to edit by hand.
+    #[default]
+    Folders,
+    /// A single file per save path, containing every entity and component.
+    /// Far fewer files, which matters once a save has thousands of entities.
+    Bundle,
+}
+
+// Manually implemented, so that saves from before SaveFormat existed can still load.
+#[derive(Serialize, Deserialize)]
+pub struct SerialisedSaveConfig {
+    path: String,
+    #[serde(default)]
+    format: SaveFormat,
+}
+
+impl SaveAndLoad for SaveConfig {
+    type Serialised = SerialisedSaveConfig;
+
+    const STRUCT_IDENT_LOWERCASE: &str = "saveconfig";
+
+    fn serialise(&self, _: &mut SerialiseEntity) -> Self::Serialised {
+        SerialisedSaveConfig {
+            path: self.path.clone(),
+            format: self.format,
+        }
+    }
+
+    fn deserialise(
+        serialised: &Self::Serialised,
+        _: &mut DeserialiseEntity,
+        _: &mut Commands,
+    ) -> Option<Self> {
+        Some(Self {
+            path: serialised.path.clone(),
+            format: serialised.format,
+        })
+    }
 }
 
-/// What stage of loading are we.
-pub enum LoadingStage<T: Asset> {
-    GotFolderHandle(Handle<LoadedFolder>),
-    GotComponentHandles(Vec<Handle<T>>),
+/// Every serialised component of every entity in a save path, stored in 1 file.
+/// The components are keyed by their STRUCT_IDENT_LOWERCASE.
+#[derive(Serialize, Deserialize, Default)]
+pub struct SaveBundle(BTreeMap<SerialisedEntity, BTreeMap<String, serde_json::Value>>);
+
+impl SaveBundle {
+    /// Writes the bundle into the folder.
+    /// Returns false if this failed.
+    fn write(&self, save_store: &dyn SaveStorage, folder_path: &Path) -> bool {
+        let serialised = ok_or_error_and_return!(
+            serde_json::to_vec_pretty(self),
+            "Tried to save a bundle. During serialisation got this error:",
+            false
+        );
+        ok_or_error_and_return!(
+            save_store.write(&folder_path.join(BUNDLE_FILE_NAME), &serialised),
+            "Tried to write a bundle. Got this error:",
+            false
+        );
+
+        true
+    }
 }
 
+/// Bundles that are being filled by each SaveAndLoad::save, keyed by their save path.
+/// They are written to disk in save_commit, once every component has been added.
+#[derive(Resource, Default)]
+pub struct SaveBundles(HashMap<String, SaveBundle>);
+
 pub trait SaveAndLoad: Sized + Component {
-    type Serialised: Serialize + DeserializeOwned + Asset;
+    type Serialised: Serialize + DeserializeOwned;
 
     const STRUCT_IDENT_LOWERCASE: &str;
-    const FILE_EXTENSION: &str;
+    /// The version of Serialised. This is written next to every component.
+    /// Whenever Serialised changes shape, increase this with #[save(version = N)], and add a migration from the previous version.
+    const VERSION: u32 = 0;
 
     fn serialise(&self, serialise_entity: &mut SerialiseEntity) -> Self::Serialised;
+    /// Returns None if there is nothing to insert, because the component was put on the entity some other way.
+    /// Parent does this, as only the hierarchy can make one.
     fn deserialise(
         serialised: &Self::Serialised,
         deserialise_entity: &mut DeserialiseEntity,
         commands: &mut Commands,
-    ) -> Self;
+    ) -> Option<Self>;
 
     fn save(
-        values: Query<(Entity, &SaveConfig, &Self)>,
+        values: Query<(Entity, &SaveConfig, Ref<Self>)>,
         mut save_components: EventReader<SaveComponents>,
-        mut serialise_entity: ResMut<SerialiseEntity>,
+        mut saved_paths: ResMut<SavedPaths>,
+        mut save_transactions: ResMut<SaveTransactions>,
+        mut save_bundles: ResMut<SaveBundles>,
+        save_store: Res<SaveStore>,
+        system_change_tick: SystemChangeTick,
     ) {
         save_components.read().for_each(|save_components| {
+            let destination = &save_components.destination;
+            let saved_path = saved_paths.0.entry(destination.clone()).or_default();
+            let mut files = HashSet::new();
+
             values.iter().for_each(|(entity, save_config, value)| {
                 // find the save configs whose paths match the path you want to save
                 // get or create entity folder at the path
                 // create component file in it
 
-                if save_config.path != save_components.0 {
+                if !SavePath::same(&save_config.path, &save_components.path) {
+                    return;
+                }
+
+                saved_path.serialise_entity.serialising = Some(entity);
+                let entity = saved_path.serialise_entity.convert(entity);
+                files.insert(entity.0);
+
+                // A bundle is always written whole, so only folders can skip unchanged components.
+                if save_config.format == SaveFormat::Folders
+                    && !saved_path.needs_writing(entity, &value, system_change_tick.this_run())
+                {
                     return;
                 }
 
-                let entity = serialise_entity.convert(entity);
+                let serialised = value.serialise(&mut saved_path.serialise_entity);
+                let serialised = Versioned {
+                    version: Self::VERSION,
+                    component: &serialised,
+                };
+
+                if save_config.format == SaveFormat::Bundle {
+                    let serialised = match serde_json::to_value(&serialised) {
+                        Ok(serialised) => serialised,
+                        Err(error) => {
+                            error!(
+                                "Tried to add a component to a bundle. During serialisation got \
+                                 this error: {error}"
+                            );
+                            save_transactions.fail(destination);
+                            return;
+                        }
+                    };
 
-                let serialised = value.serialise(&mut serialise_entity);
+                    save_bundles
+                        .0
+                        .entry(destination.clone())
+                        .or_default()
+                        .0
+                        .entry(entity)
+                        .or_default()
+                        .insert(Self::STRUCT_IDENT_LOWERCASE.to_string(), serialised);
+                    return;
+                }
 
                 // Each entity should have only 1 of each component, so the file is unique.
-                SaveComponents::to_serialised_entity(
+                if !SaveComponents::to_serialised_entity(
+                    &**save_store,
                     &serialised,
-                    entity,
-                    &save_config.path,
+                    &saved_path.folder_name(entity),
+                    destination,
                     Self::STRUCT_IDENT_LOWERCASE,
-                );
+                ) {
+                    save_transactions.fail(destination);
+                }
             });
+
+            saved_path.saved::<Self>(files, system_change_tick.this_run());
         });
     }
 
     fn load(
         mut commands: Commands,
-        mut deserialise_entity: ResMut<DeserialiseEntity>,
-
+        migrations: Res<Migrations>,
         mut load_components: EventReader<LoadComponents>,
         mut load_finish: EventWriter<LoadFinish>,
-
-        asset_server: Res<AssetServer>,
-        serialised: Res<Assets<Self::Serialised>>,
-        folders: Res<Assets<LoadedFolder>>,
-
-        // A vec, so that if multiple folders are loaded, we can cope with the throughput.
-        mut loading_stages: Local<Vec<LoadingStage<Self::Serialised>>>,
+        mut component_migrated: EventWriter<ComponentMigrated>,
+        mut load_progress: ResMut<LoadProgress>,
     ) {
         load_components.read().for_each(|load_components| {
-            // If multiple systems load the same folder, they will all be given the same handle.
-            // This therefore does not waste effort loading the folder multiple times.
-            loading_stages.push(LoadingStage::GotFolderHandle(asset_server.load_folder(
-                Path::new(SAVE_PATH_RELATIVE_TO_ASSETS).join(&load_components.0),
-            )));
-        });
-
-        let mut loading_stage_index = loading_stages.len();
-
-        while loading_stage_index != 0 {
-            loading_stage_index -= 1;
-
-            let loading_stage = &mut loading_stages[loading_stage_index];
-
-            if let LoadingStage::GotFolderHandle(folder_handle) = loading_stage {
-                let Some(folder) = folders.get(folder_handle) else {
-                    continue;
-                };
-
-                let mut component_handles = vec![];
-
-                folder.handles.iter().for_each(|handle| {
-                    if let Ok(handle) = handle.clone().try_typed::<Self::Serialised>() {
-                        component_handles.push(handle);
-                    }
-                });
+            let save_path = &load_components.path;
 
-                *loading_stage = LoadingStage::GotComponentHandles(component_handles);
+            if !load_components.filter.allows::<Self>() {
+                load_progress.finish_type(save_path);
+                return;
             }
 
-            if let LoadingStage::GotComponentHandles(component_handles) = loading_stage {
-                let mut handle_index = component_handles.len();
-
-                while handle_index != 0 {
-                    handle_index -= 1;
-                    let component_handle = &component_handles[handle_index];
-
-                    let Some(path) = component_handle.path() else {
-                        error!(
-                            "Tried to get the path of a component from its handle. Something went \
-                             wrong though. This error should never happen."
-                        );
-                        continue;
+            let mut deserialise_entity = load_components.deserialise_entity();
+            load_components
+                .files
+                .components
+                .get(Self::STRUCT_IDENT_LOWERCASE)
+                .into_iter()
+                .flatten()
+                .for_each(|component_file| {
+                    let Some((serialised, migrated)) = migrations.read::<Self>(
+                        &component_file.value,
+                        &component_file.file,
+                        component_file.entity,
+                        &mut component_migrated,
+                    ) else {
+                        return;
                     };
 
-                    if let Some(serialised) = serialised.get(component_handle) {
-                        let Some(path) = path.parent() else {
-                            error!(
-                                "Tried to get the parent folder of a serialised component, and \
-                                 for some reason it does not have a parent."
-                            );
-                            continue;
-                        };
+                    if migrated {
+                        deserialise_entity
+                            .migrated
+                            .entry(TypeId::of::<Self>())
+                            .or_default()
+                            .insert(component_file.entity.0);
+                    }
 
-                        let Some(entity_folder_name) = path.path().file_name() else {
-                            error!(
-                                "Tried to get the name of the parent folder of a serialised \
-                                 component, and for some reason we failed."
-                            );
-                            continue;
-                        };
-                        let Some(entity_folder_name) = entity_folder_name.to_str() else {
-                            error!("Tried to get a folder name but found it was invalid.");
-                            continue;
-                        };
-
-                        let Ok(serialised_entity) = entity_folder_name.parse() else {
-                            error!("An entity's folder name could not be parsed as a number.");
-                            continue;
-                        };
-                        let serialised_entity = SerialisedEntity(serialised_entity);
-
-                        let deserialised =
-                            Self::deserialise(serialised, &mut deserialise_entity, &mut commands);
-
-                        let entity = deserialise_entity.convert(serialised_entity, &mut commands);
-                        commands.entity(entity).insert(deserialised);
-                        load_finish.send(LoadFinish {
-                            entity,
-                            type_id: TypeId::of::<Self>(),
-                        });
+                    insert_deserialised::<Self>(
+                        &serialised,
+                        component_file.entity,
+                        &mut deserialise_entity,
+                        &mut commands,
+                        &mut load_finish,
+                    );
+                    load_progress.loaded_component(save_path);
+                });
 
-                        // Iterating backwards, so this is safe.
-                        component_handles.swap_remove(handle_index);
-                    }
-                }
+            load_progress.finish_type(save_path);
+        });
+    }
+}
 
-                if component_handles.len() == 0 {
-                    // Iterating backwards, so this is safe.
-                    loading_stages.swap_remove(loading_stage_index);
-                }
-            } else {
-                unreachable!(
-                    "The previous if statement checks for the only other loading stage, so this \
-                     will not happen."
-                );
-            }
-        }
+/// Inserts the deserialised component onto the entity, and lets everyone know it has loaded.
+fn insert_deserialised<T: SaveAndLoad>(
+    serialised: &T::Serialised,
+    serialised_entity: SerialisedEntity,
+    deserialise_entity: &mut DeserialiseEntity,
+    commands: &mut Commands,
+    load_finish: &mut EventWriter<LoadFinish>,
+) {
+    deserialise_entity.referrer = Some((
+        Referrer::Entity(serialised_entity),
+        T::STRUCT_IDENT_LOWERCASE,
+    ));
+    let deserialised = T::deserialise(serialised, deserialise_entity, commands);
+    deserialise_entity.referrer = None;
+
+    deserialise_entity
+        .components
+        .entry(TypeId::of::<T>())
+        .or_default()
+        .insert(serialised_entity.0);
+    let entity = deserialise_entity.convert(serialised_entity, commands);
+    if let Some(deserialised) = deserialised {
+        commands.entity(entity).insert(deserialised);
     }
+    load_finish.send(LoadFinish {
+        entity,
+        type_id: TypeId::of::<T>(),
+    });
 }
 
 pub fn setup_app_for_saving_and_loading<T: SaveAndLoad>(app: &mut App) -> &mut App {
-    app.add_plugins(JsonAssetPlugin::<T::Serialised>::new(&[T::FILE_EXTENSION]));
-    app.add_systems(crate::Update_SaveAndLoad, (T::save, T::load));
+    app.init_resource::<LoadProgress>();
+    app.world_mut().resource_mut::<LoadProgress>().types += 1;
+    app.init_resource::<SaveTypes>();
+    app.world_mut()
+        .resource_mut::<SaveTypes>()
+        .add_component::<T>();
+    app.init_resource::<Autosave>();
+    app.add_systems(
+        crate::Update_SaveAndLoad,
+        (T::save, T::load, autosave::mark_changed::<T>),
+    );
     app
 }
-
-save_and_load_external! {
-    pub struct Transform {
-        pub translation: Vec3,
-        pub rotation: Quat,
-        pub scale: Vec3,
-    }
-}
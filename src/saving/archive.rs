@@ -0,0 +1,279 @@
+use std::collections::BTreeMap;
+
+use serde_json::Value;
+
+use super::{
+    component_name,
+    metadata::{SaveMetadata, METADATA_FILE_NAME},
+    migration::Versioned,
+    resources::{self, SaveAndLoadResource},
+    save_id, SaveAndLoad, SaveBundle, SaveConfig, BUNDLE_FILE_NAME, SAVE_PATH, STAGING_PATH,
+};
+use crate::prelude::*;
+
+pub mod prelude {
+    pub use super::ArchiveFinished;
+}
+
+/// Archives are kept in this folder, inside the SaveStore.
+const ARCHIVE_PATH: &str = "./archives/";
+/// Added to the name of every archive.
+const ARCHIVE_EXTENSION: &str = ".save.json";
+
+/// A whole save path in 1 file, so that it can be passed around.
+#[derive(Serialize, Deserialize)]
+struct SaveArchive {
+    metadata: SaveMetadata,
+    /// Every other file of the save, keyed by where it is in the save path. Everything saved is json.
+    files: BTreeMap<String, Value>,
+}
+
+/// Sent once Save::export or Load::import has finished, whether or not it succeeded.
+/// Names that aren't allowed send InvalidSavePath instead.
+#[derive(Event, Clone, Debug)]
+pub struct ArchiveFinished {
+    pub archive: String,
+    pub path: String,
+    /// True if the path was exported into the archive, false if the archive was imported into the path.
+    pub exported: bool,
+    /// Why it failed, if it did. A successful import goes on to load the path, which sends LoadFinished.
+    pub error: Option<String>,
+}
+
+impl ArchiveFinished {
+    /// Logs any error, and sends this through commands, like InvalidSavePath.
+    pub(super) fn send(
+        archive: &SavePath,
+        path: &SavePath,
+        exported: bool,
+        result: Result<(), String>,
+        commands: &mut Commands,
+    ) {
+        let error = result.err();
+        if let Some(error) = &error {
+            let action = if exported { "export" } else { "import" };
+            error!("Tried to {action} {archive} and got this error: {error}");
+        }
+
+        commands.send_event(ArchiveFinished {
+            archive: archive.to_string(),
+            path: path.to_string(),
+            exported,
+            error,
+        });
+    }
+}
+
+/// Checks that a serialised component can be deserialised, migrating it first if needed.
+type CheckComponent = fn(&Migrations, &Value) -> Result<(), String>;
+/// Checks that a serialised resource can be deserialised.
+type CheckResource = fn(&Value) -> Result<(), String>;
+
+/// Every type that can be loaded, keyed by STRUCT_IDENT_LOWERCASE.
+/// Used to check that archives only contain what can be loaded, before they are accepted.
+#[derive(Resource, Default)]
+pub struct SaveTypes {
+    components: HashMap<&'static str, CheckComponent>,
+    resources: HashMap<&'static str, CheckResource>,
+}
+
+impl SaveTypes {
+    pub(super) fn add_component<T: SaveAndLoad>(&mut self) {
+        self.components
+            .insert(T::STRUCT_IDENT_LOWERCASE, |migrations, value| {
+                migrations.deserialise::<T>(value).map(|_| ())
+            });
+    }
+
+    pub(super) fn add_resource<T: SaveAndLoadResource>(&mut self) {
+        self.resources.insert(T::STRUCT_IDENT_LOWERCASE, |value| {
+            serde_json::from_value::<T::Serialised>(value.clone())
+                .map(|_| ())
+                .map_err(|error| error.to_string())
+        });
+    }
+
+    /// Checks a file from an archive, and moves its entities into the path.
+    fn check_file(
+        &self,
+        migrations: &Migrations,
+        file: &str,
+        value: Value,
+        path: &SavePath,
+    ) -> Result<Value, String> {
+        let checked = match file.split('/').collect::<Vec<_>>().as_slice() {
+            [folder, file_name] => {
+                if save_id::parse_folder_name(folder).is_none() {
+                    return Err(format!("{folder} isn't an entity's folder."));
+                }
+                let Some(component) = component_name(file_name) else {
+                    return Err(format!("{file} isn't a component."));
+                };
+                self.check_component(migrations, component, value, path)
+            }
+            [BUNDLE_FILE_NAME] => {
+                let mut save_bundle = serde_json::from_value::<SaveBundle>(value)
+                    .map_err(|error| error.to_string())?;
+                for components in save_bundle.0.values_mut() {
+                    for (component, value) in components.iter_mut() {
+                        *value = self.check_component(migrations, component, value.take(), path)?;
+                    }
+                }
+                serde_json::to_value(save_bundle).map_err(|error| error.to_string())
+            }
+            [file_name] if file_name.ends_with(resources::FILE_SUFFIX) => {
+                let resource = file_name.trim_end_matches(resources::FILE_SUFFIX);
+                let Some(check) = self.resources.get(resource) else {
+                    return Err(format!("{resource} isn't a resource that can be loaded."));
+                };
+                check(&value).map(|()| value)
+            }
+            _ => Err("Saves don't contain files like this.".to_string()),
+        };
+
+        checked.map_err(|error| format!("{file} is invalid. {error}"))
+    }
+
+    /// Checks that the component can be loaded.
+    /// Entities belong to the path in their SaveConfig, so it is changed to the path the archive is unpacked into.
+    fn check_component(
+        &self,
+        migrations: &Migrations,
+        component: &str,
+        value: Value,
+        path: &SavePath,
+    ) -> Result<Value, String> {
+        let Some(check) = self.components.get(component) else {
+            return Err(format!("{component} isn't a component that can be loaded."));
+        };
+        check(migrations, &value)?;
+
+        if component != SaveConfig::STRUCT_IDENT_LOWERCASE {
+            return Ok(value);
+        }
+
+        let (mut save_config, _) = migrations.deserialise::<SaveConfig>(&value)?;
+        save_config.path = path.to_string();
+        serde_json::to_value(Versioned {
+            version: SaveConfig::VERSION,
+            component: &save_config,
+        })
+        .map_err(|error| error.to_string())
+    }
+}
+
+fn archive_file(archive: &SavePath) -> PathBuf {
+    Path::new(ARCHIVE_PATH).join(format!("{archive}{ARCHIVE_EXTENSION}"))
+}
+
+fn read_value(save_store: &dyn SaveStorage, file: &Path) -> Result<Value, String> {
+    let bytes = save_store
+        .read(file)
+        .map_err(|error| format!("Tried to read {}. {error}", file.display()))?;
+    serde_json::from_slice(&bytes)
+        .map_err(|error| format!("{} is invalid. {error}", file.display()))
+}
+
+/// Packs every file of the save at the path into the archive, replacing it if it exists.
+pub(super) fn export(
+    save_store: &dyn SaveStorage,
+    path: &SavePath,
+    archive: &SavePath,
+) -> Result<(), String> {
+    let folder = Path::new(SAVE_PATH).join(path);
+
+    let metadata = read_value(save_store, &folder.join(METADATA_FILE_NAME))
+        .and_then(|metadata| serde_json::from_value(metadata).map_err(|error| error.to_string()))
+        .map_err(|error| format!("{path} has no metadata, so save it again first. {error}"))?;
+
+    let mut files = BTreeMap::new();
+    for entry in save_store
+        .read_dir(&folder)
+        .map_err(|error| format!("Tried to read {path}. {error}"))?
+    {
+        if !entry.is_folder {
+            if entry.name != METADATA_FILE_NAME {
+                files.insert(
+                    entry.name.clone(),
+                    read_value(save_store, &folder.join(&entry.name))?,
+                );
+            }
+            continue;
+        }
+
+        let entity_folder = folder.join(&entry.name);
+        for file in save_store
+            .read_dir(&entity_folder)
+            .map_err(|error| format!("Tried to read {}. {error}", entity_folder.display()))?
+        {
+            files.insert(
+                format!("{}/{}", entry.name, file.name),
+                read_value(save_store, &entity_folder.join(&file.name))?,
+            );
+        }
+    }
+
+    let serialised = serde_json::to_vec_pretty(&SaveArchive { metadata, files })
+        .map_err(|error| error.to_string())?;
+    save_store
+        .write(&archive_file(archive), &serialised)
+        .map_err(|error| format!("Tried to write {archive}. {error}"))
+}
+
+/// Unpacks the archive into the path, which must not have a save yet.
+/// Nothing is written unless every file in the archive can be loaded.
+pub(super) fn import(
+    save_store: &dyn SaveStorage,
+    save_types: &SaveTypes,
+    migrations: &Migrations,
+    archive: &SavePath,
+    path: &SavePath,
+) -> Result<(), String> {
+    let save_path = Path::new(SAVE_PATH).join(path);
+    if save_store.exists(&save_path).unwrap_or(true) {
+        return Err(format!("{path} already has a save."));
+    }
+
+    let SaveArchive {
+        mut metadata,
+        files,
+    } = serde_json::from_value(read_value(save_store, &archive_file(archive))?)
+        .map_err(|error| format!("{archive} isn't an archive. {error}"))?;
+
+    let files = files
+        .into_iter()
+        .map(|(file, value)| {
+            let value = save_types.check_file(migrations, &file, value, path)?;
+            Ok((file, value))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    // Written to the staging folder first, so a half unpacked archive never looks like a save.
+    let staging_path = Path::new(STAGING_PATH).join(path);
+    if save_store.exists(&staging_path).unwrap_or(false) {
+        save_store
+            .remove_dir_all(&staging_path)
+            .map_err(|error| error.to_string())?;
+    }
+
+    for (file, value) in files {
+        let serialised = serde_json::to_vec_pretty(&value).map_err(|error| error.to_string())?;
+        save_store
+            .write(&staging_path.join(file), &serialised)
+            .map_err(|error| error.to_string())?;
+    }
+
+    metadata.path = path.to_string();
+    if !metadata.write(save_store, &staging_path) {
+        return Err(format!("Tried to write the metadata of {path}."));
+    }
+
+    if let Some(parent) = save_path.parent() {
+        save_store
+            .create_dir_all(parent)
+            .map_err(|error| error.to_string())?;
+    }
+    save_store
+        .rename(&staging_path, &save_path)
+        .map_err(|error| error.to_string())
+}
@@ -0,0 +1,210 @@
+use bevy::utils::HashSet;
+
+use super::{
+    InvalidSavePath, LoadFinished, LoadProgress, SaveAndLoadResource, SaveFinished, SavePrepare,
+};
+use crate::prelude::*;
+
+pub mod prelude {
+    pub use super::{Autosave, Autosaved};
+}
+
+/// Autosaves are saved in this folder, relative to SAVE_PATH. Reserved, so no save path can start with it.
+pub(super) const AUTOSAVE_PATH: &str = "autosaves";
+
+/// Where a slot of a path is autosaved to, relative to SAVE_PATH.
+pub(super) fn destination(path: &SavePath, slot: usize) -> SavePath {
+    SavePath::reserved(AUTOSAVE_PATH, path, slot)
+}
+
+/// Whether the destination is an autosave slot. The slots already rotate, so they aren't backed up as well.
+pub(super) fn is_slot(destination: &str) -> bool {
+    Path::new(destination).starts_with(AUTOSAVE_PATH)
+}
+
+/// Periodically saves paths into rotating slots, so a bad autosave never replaces the only good one.
+/// Autosaves never touch the save itself. Load them with Load::autosave.
+/// A path is skipped if it has no entities, or nothing in it has changed since it was last autosaved.
+#[derive(Resource)]
+pub struct Autosave {
+    /// How often to autosave.
+    pub interval: EveryTime,
+    /// The save paths to autosave. Nothing is autosaved while this is empty.
+    /// Paths that aren't allowed send InvalidSavePath each time they would have been autosaved.
+    pub paths: Vec<String>,
+    /// How many slots each path rotates through.
+    pub slots: usize,
+    next_slot: usize,
+    /// Autosaves that haven't been committed yet. (destination, (path, slot))
+    in_progress: HashMap<String, (String, usize)>,
+    /// The paths that have changed since they were last autosaved, as written in paths.
+    changed: HashSet<String>,
+}
+
+impl Autosave {
+    fn mark_changed(&mut self, path: &str) {
+        let Some(path) = self.paths.iter().find(|other| SavePath::same(other, path)) else {
+            return;
+        };
+        if !self.changed.contains(path) {
+            self.changed.insert(path.clone());
+        }
+    }
+}
+
+impl Default for Autosave {
+    fn default() -> Self {
+        Self {
+            interval: EveryTime::new(Duration::from_secs(5 * 60), Duration::ZERO),
+            paths: vec![],
+            slots: 3,
+            next_slot: 0,
+            in_progress: HashMap::default(),
+            changed: HashSet::default(),
+        }
+    }
+}
+
+/// Sent after each autosave of a path has been committed, or has failed to be.
+#[derive(Event, Debug)]
+pub struct Autosaved {
+    pub path: String,
+    pub slot: usize,
+    pub succeeded: bool,
+}
+
+/// Whether changes are from loading, rather than from playing. Loads insert and despawn entities until the frame
+/// after they finish, once the commands of finish_loads have been applied.
+/// Anything else that changes meanwhile is missed, but only for those few frames.
+fn loading(load_progress: &LoadProgress, load_finished: &mut EventReader<LoadFinished>) -> bool {
+    let loading = load_progress.is_loading() || !load_finished.is_empty();
+    load_finished.clear();
+    loading
+}
+
+/// Remembers which paths T changed in, so unchanged ones aren't autosaved again.
+/// A despawned entity can't say which path it was in, so despawning one counts as every path changing.
+pub(super) fn mark_changed<T: Component>(
+    changed: Query<&SaveConfig, Changed<T>>,
+    mut removed: RemovedComponents<T>,
+    save_configs: Query<&SaveConfig>,
+    load_progress: Res<LoadProgress>,
+    mut load_finished: EventReader<LoadFinished>,
+    mut autosave: ResMut<Autosave>,
+) {
+    // Changed is since this last ran, so returning skips what happened meanwhile.
+    if loading(&load_progress, &mut load_finished) || autosave.changed.len() >= autosave.paths.len()
+    {
+        removed.clear();
+        return;
+    }
+
+    changed
+        .iter()
+        .for_each(|save_config| autosave.mark_changed(&save_config.path));
+
+    removed
+        .read()
+        .for_each(|entity| match save_configs.get(entity) {
+            Ok(save_config) => autosave.mark_changed(&save_config.path),
+            Err(_) => {
+                let paths = autosave.paths.clone();
+                autosave.changed.extend(paths);
+            }
+        });
+}
+
+/// Remembers that the path of T changed, so it is autosaved again.
+pub(super) fn mark_resource_changed<T: SaveAndLoadResource>(
+    resource: Option<Res<T>>,
+    load_progress: Res<LoadProgress>,
+    mut load_finished: EventReader<LoadFinished>,
+    mut autosave: ResMut<Autosave>,
+) {
+    if loading(&load_progress, &mut load_finished) {
+        return;
+    }
+
+    if resource.is_some_and(|resource| resource.is_changed()) {
+        autosave.mark_changed(T::PATH);
+    }
+}
+
+pub(super) fn autosave(
+    mut autosave: ResMut<Autosave>,
+    time: Res<Time>,
+    load_progress: Res<LoadProgress>,
+    save_configs: Query<&SaveConfig>,
+    mut save_prepare: EventWriter<SavePrepare>,
+    mut commands: Commands,
+) {
+    // Saving halfway through a load would save half the entities. It will autosave once the load finishes instead.
+    if autosave.paths.is_empty() || autosave.slots == 0 || load_progress.is_loading() {
+        return;
+    }
+
+    let mut due = false;
+    autosave.interval.tick(time.delta());
+    autosave.interval.run(|| due = true);
+    autosave.interval.finish_running();
+
+    if !due {
+        return;
+    }
+
+    let autosave = autosave.into_inner();
+    let slot = autosave.next_slot % autosave.slots;
+    let mut autosaved_any = false;
+
+    autosave.paths.iter().for_each(|path| {
+        let has_entities = save_configs
+            .iter()
+            .any(|save_config| SavePath::same(&save_config.path, path));
+        if !has_entities || !autosave.changed.remove(path) {
+            return;
+        }
+
+        let Some(path) = InvalidSavePath::check(path, &mut commands) else {
+            return;
+        };
+        let destination = destination(&path, slot);
+        autosave
+            .in_progress
+            .insert(destination.to_string(), (path.to_string(), slot));
+        autosaved_any = true;
+
+        save_prepare.send(SavePrepare {
+            path,
+            destination,
+            description: format!("Autosave {slot}"),
+            screenshot: None,
+        });
+    });
+
+    // The slot is still empty if nothing was autosaved into it.
+    if autosaved_any {
+        autosave.next_slot = (slot + 1) % autosave.slots;
+    }
+}
+
+pub(super) fn autosaved(
+    mut autosave: ResMut<Autosave>,
+    mut save_finished: EventReader<SaveFinished>,
+    mut autosaved: EventWriter<Autosaved>,
+) {
+    save_finished.read().for_each(|save_finished| {
+        let Some((path, slot)) = autosave.in_progress.remove(&save_finished.destination) else {
+            return;
+        };
+
+        if save_finished.succeeded {
+            info!("Autosaved {path} to slot {slot}.");
+        }
+
+        autosaved.send(Autosaved {
+            path,
+            slot,
+            succeeded: save_finished.succeeded,
+        });
+    });
+}
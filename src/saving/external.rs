@@ -0,0 +1,170 @@
+//! SaveAndLoad for types from other crates.
+//! save_and_load_external! needs the definition copied in, and every field public.
+//! They are registered by SavingPlugin, through setup.
+
+use bevy::sprite::Anchor;
+
+use super::{DeserialiseEntity, Referrer, SaveAndLoad, SerialiseEntity, SerialisedEntity};
+use crate::prelude::*;
+
+save_and_load_external! {
+    pub struct Transform {
+        pub translation: Vec3,
+        pub rotation: Quat,
+        pub scale: Vec3,
+    }
+}
+
+save_and_load_external! {
+    pub enum Visibility {
+        Inherited,
+        Hidden,
+        Visible,
+    }
+}
+
+// Texture atlas layouts and image modes are made in code, so they are left for whatever spawned the sprite to add back.
+save_and_load_external! {
+    pub struct Sprite {
+        #[save(map)]
+        pub image: Handle<Image>,
+        #[save(skip)]
+        pub texture_atlas: Option<TextureAtlas>,
+        pub color: Color,
+        pub flip_x: bool,
+        pub flip_y: bool,
+        pub custom_size: Option<Vec2>,
+        pub rect: Option<Rect>,
+        #[save(with = anchor)]
+        pub anchor: Anchor,
+        #[save(skip)]
+        pub image_mode: SpriteImageMode,
+    }
+}
+
+/// Anchor isn't serialisable, so this mirrors it.
+mod anchor {
+    use bevy::sprite::Anchor;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use crate::prelude::*;
+
+    #[derive(Serialize, Deserialize)]
+    enum SerialisedAnchor {
+        Center,
+        BottomLeft,
+        BottomCenter,
+        BottomRight,
+        CenterLeft,
+        CenterRight,
+        TopLeft,
+        TopCenter,
+        TopRight,
+        Custom(Vec2),
+    }
+
+    pub fn serialize<S: Serializer>(anchor: &Anchor, serializer: S) -> Result<S::Ok, S::Error> {
+        match *anchor {
+            Anchor::Center => SerialisedAnchor::Center,
+            Anchor::BottomLeft => SerialisedAnchor::BottomLeft,
+            Anchor::BottomCenter => SerialisedAnchor::BottomCenter,
+            Anchor::BottomRight => SerialisedAnchor::BottomRight,
+            Anchor::CenterLeft => SerialisedAnchor::CenterLeft,
+            Anchor::CenterRight => SerialisedAnchor::CenterRight,
+            Anchor::TopLeft => SerialisedAnchor::TopLeft,
+            Anchor::TopCenter => SerialisedAnchor::TopCenter,
+            Anchor::TopRight => SerialisedAnchor::TopRight,
+            Anchor::Custom(point) => SerialisedAnchor::Custom(point),
+        }
+        .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Anchor, D::Error> {
+        Ok(match SerialisedAnchor::deserialize(deserializer)? {
+            SerialisedAnchor::Center => Anchor::Center,
+            SerialisedAnchor::BottomLeft => Anchor::BottomLeft,
+            SerialisedAnchor::BottomCenter => Anchor::BottomCenter,
+            SerialisedAnchor::BottomRight => Anchor::BottomRight,
+            SerialisedAnchor::CenterLeft => Anchor::CenterLeft,
+            SerialisedAnchor::CenterRight => Anchor::CenterRight,
+            SerialisedAnchor::TopLeft => Anchor::TopLeft,
+            SerialisedAnchor::TopCenter => Anchor::TopCenter,
+            SerialisedAnchor::TopRight => Anchor::TopRight,
+            SerialisedAnchor::Custom(point) => Anchor::Custom(point),
+        })
+    }
+}
+
+impl SaveAndLoad for Name {
+    type Serialised = String;
+
+    const STRUCT_IDENT_LOWERCASE: &str = "name";
+
+    fn serialise(&self, _: &mut SerialiseEntity) -> Self::Serialised {
+        self.as_str().to_string()
+    }
+
+    fn deserialise(
+        serialised: &Self::Serialised,
+        _: &mut DeserialiseEntity,
+        _: &mut Commands,
+    ) -> Option<Self> {
+        Some(Name::new(serialised.clone()))
+    }
+}
+
+/// Version 1 added index.
+#[derive(Serialize, Deserialize)]
+pub struct SerialisedParent {
+    parent: SerialisedEntity,
+    /// Where the child is among its parent's Children.
+    index: usize,
+}
+
+/// Only Parent is saved. Children is rebuilt from it once the path has loaded, in the order it was saved in.
+/// Reordering Children doesn't change Parent, so incremental saves only pick up the new order once the children change.
+impl SaveAndLoad for Parent {
+    type Serialised = SerialisedParent;
+
+    const STRUCT_IDENT_LOWERCASE: &str = "parent";
+    const VERSION: u32 = 1;
+
+    fn serialise(&self, serialise_entity: &mut SerialiseEntity) -> Self::Serialised {
+        SerialisedParent {
+            parent: serialise_entity.convert(self.get()),
+            index: serialise_entity.child_index().unwrap_or_default(),
+        }
+    }
+
+    fn deserialise(
+        serialised: &Self::Serialised,
+        deserialise_entity: &mut DeserialiseEntity,
+        commands: &mut Commands,
+    ) -> Option<Self> {
+        // A reference like any other, so a parent that wasn't loaded is up to the LoadIssuePolicy.
+        deserialise_entity.convert(serialised.parent, commands);
+
+        // Parent can only be made by bevy_hierarchy, so there is nothing to insert.
+        let Some((Referrer::Entity(child), _)) = deserialise_entity.referrer else {
+            return None;
+        };
+        deserialise_entity
+            .children
+            .push((serialised.parent, serialised.index, child));
+
+        None
+    }
+}
+
+pub(super) fn setup(app: &mut App) {
+    super::setup_app_for_saving_and_loading::<Transform>(app);
+    super::setup_app_for_saving_and_loading::<Visibility>(app);
+    super::setup_app_for_saving_and_loading::<Sprite>(app);
+    super::setup_app_for_saving_and_loading::<Name>(app);
+    super::setup_app_for_saving_and_loading::<Parent>(app);
+    // Version 0 was only the parent. Children from before then keep the order they are loaded in.
+    app.add_migration::<Parent>(0, |value| {
+        *value = serde_json::json!({"parent": value.take(), "index": 0});
+        Ok(())
+    });
+}
@@ -0,0 +1,247 @@
+use std::{sync::Mutex, time::SystemTime};
+
+use bevy::utils::HashSet;
+
+use super::{
+    component_name, save_id, storage::SaveStore, DeserialiseEntity, LoadComponents, LoadFinished,
+    LoadProgress, SaveFiles, SaveFinished, SerialisedEntity, BUNDLE_FILE_NAME, SAVE_PATH,
+};
+use crate::prelude::*;
+
+pub mod prelude {
+    pub use super::HotReload;
+}
+
+/// Watches the save paths that have been loaded, so component files edited by hand show up without reloading everything.
+/// Only the changed components are deserialised again, and each sends LoadFinish like a normal load.
+/// A changed bundle reloads every component in it, as there is no telling which one changed.
+/// Resources, Parent and entities that weren't loaded are left for a full load.
+#[derive(Resource)]
+pub struct HotReload {
+    /// On by default in debug builds.
+    pub enabled: bool,
+    /// How often to check for changed files.
+    pub interval: EveryTime,
+    /// How each watched path is watched, keyed by save path.
+    watched: HashMap<String, Watched>,
+}
+
+enum Watched {
+    /// The SaveStore says which files were written.
+    Store,
+    /// The SaveStore can't, so when each file was last written is compared instead.
+    /// This has to look at every file, so storages that can watch should.
+    Modified(HashMap<PathBuf, SystemTime>),
+}
+
+impl Default for HotReload {
+    fn default() -> Self {
+        Self {
+            enabled: cfg!(debug_assertions),
+            interval: EveryTime::new(Duration::from_secs(1), Duration::ZERO),
+            watched: HashMap::default(),
+        }
+    }
+}
+
+impl HotReload {
+    /// Stops watching the path, until it is next loaded.
+    pub fn unwatch(&mut self, path: &str) {
+        if let Ok(path) = SavePath::new(path) {
+            self.watched.remove(&*path);
+        }
+    }
+}
+
+/// Starts watching paths once they have loaded.
+/// Saving replaces the folder, so saved paths are watched again, without reloading what was just saved.
+pub(super) fn watch(
+    mut hot_reload: ResMut<HotReload>,
+    mut load_finished: EventReader<LoadFinished>,
+    mut save_finished: EventReader<SaveFinished>,
+    save_store: Res<SaveStore>,
+) {
+    if !hot_reload.enabled {
+        return;
+    }
+
+    let mut paths = load_finished
+        .read()
+        .filter(|load_finished| load_finished.succeeded)
+        .map(|load_finished| load_finished.path.clone())
+        .collect::<Vec<_>>();
+    paths.extend(
+        save_finished
+            .read()
+            .map(|save_finished| save_finished.destination.clone())
+            .filter(|destination| hot_reload.watched.contains_key(destination)),
+    );
+
+    paths.into_iter().for_each(|path| {
+        let watched = if save_store.watch(&Path::new(SAVE_PATH).join(&path)) {
+            Watched::Store
+        } else {
+            Watched::Modified(modified_times(&**save_store, &path))
+        };
+        hot_reload.watched.insert(path, watched);
+    });
+}
+
+/// Checks watched paths for changed component files, and loads them onto the entities they belong to.
+pub(super) fn reload(
+    mut hot_reload: ResMut<HotReload>,
+    time: Res<Time>,
+    load_progress: Res<LoadProgress>,
+    save_store: Res<SaveStore>,
+    asset_server: Option<Res<AssetServer>>,
+    entities: Query<(Entity, &SaveId, &SaveConfig)>,
+    mut load_components: EventWriter<LoadComponents>,
+) {
+    if !hot_reload.enabled || hot_reload.watched.is_empty() {
+        return;
+    }
+
+    let mut due = false;
+    hot_reload.interval.tick(time.delta());
+    hot_reload.interval.run(|| due = true);
+    hot_reload.interval.finish_running();
+
+    if !due {
+        return;
+    }
+
+    let written = save_store.written();
+
+    hot_reload.watched.iter_mut().for_each(|(path, watched)| {
+        let folder = Path::new(SAVE_PATH).join(path);
+
+        let changed = match watched {
+            Watched::Store => written
+                .iter()
+                .filter(|file| is_watched(file, &folder))
+                .cloned()
+                .collect::<HashSet<_>>(),
+            Watched::Modified(previous) => {
+                let modified = modified_times(&**save_store, path);
+                let changed = modified
+                    .iter()
+                    .filter(|(file, modified)| previous.get(*file) != Some(*modified))
+                    .map(|(file, _)| file.clone())
+                    .collect();
+                *previous = modified;
+                changed
+            }
+        };
+
+        // Its entities are about to be replaced anyway. It is looked at again once it has loaded.
+        if changed.is_empty() || load_progress.path(path).is_some() {
+            return;
+        }
+
+        let mut deserialise_entity = DeserialiseEntity::new(asset_server.as_deref().cloned());
+        deserialise_entity.entities = entities
+            .iter()
+            .filter(|(.., save_config)| SavePath::same(&save_config.path, path))
+            .map(|(entity, save_id, _)| (save_id.0, entity))
+            .collect();
+
+        let mut save_files = SaveFiles::default();
+        changed.into_iter().for_each(|file| {
+            info!("Reloading {}.", file.display());
+
+            if file
+                .file_name()
+                .is_some_and(|name| name == BUNDLE_FILE_NAME)
+            {
+                save_files.read_bundle(&**save_store, file, |entity| {
+                    deserialise_entity.entities.contains_key(&entity.0)
+                });
+                return;
+            }
+
+            let Some(save_id) = file
+                .parent()
+                .and_then(|folder| folder.file_name())
+                .and_then(|name| name.to_str())
+                .and_then(save_id::parse_folder_name)
+            else {
+                return;
+            };
+
+            if !deserialise_entity.entities.contains_key(&save_id) {
+                info!(
+                    "{} belongs to an entity that wasn't loaded, so it won't be until {path} is \
+                     loaded again.",
+                    file.display()
+                );
+                return;
+            }
+
+            save_files.read_component(&**save_store, SerialisedEntity(save_id), file);
+        });
+
+        load_components.send(LoadComponents {
+            path: path.clone(),
+            files: Arc::new(save_files),
+            filter: ComponentFilter::All,
+            deserialise_entity: Arc::new(Mutex::new(deserialise_entity)),
+        });
+    });
+}
+
+/// Whether the file is a component or bundle directly in the path's folder, or in one of its entity folders.
+fn is_watched(file: &Path, folder: &Path) -> bool {
+    // The storage may or may not start paths with ./, so that is ignored.
+    let normalise = |path: &Path| {
+        path.components()
+            .filter(|component| *component != std::path::Component::CurDir)
+            .collect::<PathBuf>()
+    };
+    let Ok(inside) = normalise(file)
+        .strip_prefix(normalise(folder))
+        .map(Path::to_path_buf)
+    else {
+        return false;
+    };
+    let names = inside
+        .iter()
+        .filter_map(|name| name.to_str())
+        .collect::<Vec<_>>();
+
+    match names.as_slice() {
+        [name] => *name == BUNDLE_FILE_NAME,
+        [_, name] => component_name(name).is_some(),
+        _ => false,
+    }
+}
+
+/// When each component file in the path's entity folders, and its bundle, was last written.
+fn modified_times(save_store: &dyn SaveStorage, path: &str) -> HashMap<PathBuf, SystemTime> {
+    let folder = Path::new(SAVE_PATH).join(path);
+    let Ok(entries) = save_store.read_dir(&folder) else {
+        return HashMap::default();
+    };
+
+    entries
+        .into_iter()
+        .flat_map(|entry| {
+            if !entry.is_folder {
+                return if entry.name == BUNDLE_FILE_NAME {
+                    vec![folder.join(entry.name)]
+                } else {
+                    vec![]
+                };
+            }
+
+            let entity_folder = folder.join(&entry.name);
+            save_store
+                .read_dir(&entity_folder)
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|file| component_name(&file.name).is_some())
+                .map(|file| entity_folder.join(file.name))
+                .collect()
+        })
+        .filter_map(|file| Some((file.clone(), save_store.modified(&file).ok()?)))
+        .collect()
+}
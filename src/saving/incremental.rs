@@ -0,0 +1,252 @@
+use bevy::{ecs::component::Tick, utils::HashSet};
+
+use super::{
+    metadata::{SaveMetadata, TypeMetadata},
+    DeserialiseEntity, SaveAndLoad, SerialiseEntity, SerialisedEntity, SAVE_PATH, STAGING_PATH,
+};
+use crate::prelude::*;
+
+/// What is known about the files of each save path, so that a save only has to write what changed since the last one.
+#[derive(Resource, Default)]
+pub struct SavedPaths(pub(super) HashMap<String, SavedPath>);
+
+#[derive(Default)]
+pub struct SavedPath {
+    /// Rebuilt from SaveIds before each save. New SaveIds are given out from it.
+    pub(super) serialise_entity: SerialiseEntity,
+    /// The folder each entity is saved in. Kept between incremental saves, so a renamed entity doesn't need to move.
+    pub(super) folder_names: HashMap<u32, String>,
+    /// Whether the save on disk matches ticks and files. Only then can a save be incremental.
+    pub(super) on_disk: bool,
+    /// Whether the current save only writes what changed.
+    pub(super) incremental: bool,
+    /// Written into the metadata of the current save.
+    pub(super) description: String,
+    /// Written into the metadata of the current save.
+    pub(super) screenshot: Option<String>,
+    /// When each type was last saved or loaded.
+    ticks: HashMap<TypeId, Tick>,
+    /// The serialised entities that have a file for each type.
+    files: HashMap<TypeId, HashSet<u32>>,
+    /// Component files to delete once the current save is committed.
+    /// (serialised entity, STRUCT_IDENT_LOWERCASE)
+    removed: Vec<(u32, &'static str)>,
+    /// The STRUCT_IDENT_LOWERCASE and VERSION of each type that has been saved.
+    types: HashMap<TypeId, (&'static str, u32)>,
+}
+
+impl SavedPath {
+    /// Called before a save. The save is incremental if the previous one is still on disk.
+    pub(super) fn prepare(&mut self, save_exists: bool) {
+        self.incremental = self.on_disk && save_exists;
+        self.removed.clear();
+
+        if !self.incremental {
+            self.forget();
+        }
+    }
+
+    /// Forgets everything except the serialised entities, so the next save writes everything.
+    pub(super) fn forget(&mut self) {
+        self.on_disk = false;
+        self.ticks.clear();
+        self.files.clear();
+        self.folder_names.clear();
+    }
+
+    /// The folder that the entity is saved in.
+    pub(super) fn folder_name(&self, entity: SerialisedEntity) -> String {
+        self.folder_names
+            .get(&entity.0)
+            .cloned()
+            .unwrap_or_else(|| entity.0.to_string())
+    }
+
+    /// Whether the component has to be written. Everything has to be, unless the save is incremental.
+    pub(super) fn needs_writing<T: SaveAndLoad>(
+        &self,
+        entity: SerialisedEntity,
+        value: &Ref<T>,
+        this_run: Tick,
+    ) -> bool {
+        if !self.incremental {
+            return true;
+        }
+
+        let type_id = TypeId::of::<T>();
+        let Some(last_saved) = self.ticks.get(&type_id) else {
+            return true;
+        };
+
+        let has_file = self
+            .files
+            .get(&type_id)
+            .is_some_and(|files| files.contains(&entity.0));
+
+        !has_file || value.last_changed().is_newer_than(*last_saved, this_run)
+    }
+
+    /// Called once T has been saved. Remembers which files now exist, and marks any that shouldn't for deletion.
+    /// This catches removed components, despawned entities, and entities moved to other save paths.
+    pub(super) fn saved<T: SaveAndLoad>(&mut self, files: HashSet<u32>, this_run: Tick) {
+        let type_id = TypeId::of::<T>();
+        self.types
+            .insert(type_id, (T::STRUCT_IDENT_LOWERCASE, T::VERSION));
+
+        if let Some(previous_files) = self.files.get(&type_id) {
+            self.removed.extend(
+                previous_files
+                    .difference(&files)
+                    .map(|entity| (*entity, T::STRUCT_IDENT_LOWERCASE)),
+            );
+        }
+
+        self.files.insert(type_id, files);
+        self.ticks.insert(type_id, this_run);
+    }
+
+    /// Describes the current save, once every type has been saved.
+    /// Counts everything in the save, not only what an incremental save wrote.
+    pub(super) fn metadata(&self, path: &str, playtime: Duration) -> SaveMetadata {
+        let types = self
+            .files
+            .iter()
+            .filter(|(_, files)| !files.is_empty())
+            .filter_map(|(type_id, files)| {
+                let (name, version) = self.types.get(type_id)?;
+                Some((
+                    name.to_string(),
+                    TypeMetadata {
+                        version: *version,
+                        components: files.len(),
+                    },
+                ))
+            })
+            .collect();
+        let entities = self.files.values().flatten().collect::<HashSet<_>>().len();
+
+        SaveMetadata::new(
+            path.to_string(),
+            types,
+            entities,
+            self.description.clone(),
+            playtime,
+            self.screenshot.clone(),
+        )
+    }
+
+    /// Copies everything from the previous save that the current one didn't write into the staging folder, except removed components.
+    /// The staging folder then holds the whole save, so it can be committed like a full one.
+    pub(super) fn copy_unchanged(
+        &self,
+        save_store: &dyn SaveStorage,
+        path: &str,
+    ) -> std::io::Result<()> {
+        let staging_path = Path::new(STAGING_PATH).join(path);
+        let save_path = Path::new(SAVE_PATH).join(path);
+        let removed = self
+            .removed
+            .iter()
+            .map(|(entity, component)| {
+                Path::new(&self.folder_name(SerialisedEntity(*entity)))
+                    .join(format!("component.{component}.json"))
+            })
+            .collect::<HashSet<_>>();
+
+        for entry in save_store.read_dir(&save_path)? {
+            let source = save_path.join(&entry.name);
+            let destination = staging_path.join(&entry.name);
+
+            if !entry.is_folder {
+                Self::copy_if_missing(save_store, &source, &destination)?;
+                continue;
+            }
+
+            // An entity whose components were all removed is left out entirely.
+            for file in save_store.read_dir(&source)? {
+                if removed.contains(&Path::new(&entry.name).join(&file.name)) {
+                    continue;
+                }
+
+                Self::copy_if_missing(
+                    save_store,
+                    &source.join(&file.name),
+                    &destination.join(&file.name),
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Storage can't copy, so the file is read and written again. Whatever the current save wrote is newer, so it is kept.
+    fn copy_if_missing(
+        save_store: &dyn SaveStorage,
+        source: &Path,
+        destination: &Path,
+    ) -> std::io::Result<()> {
+        if save_store.exists(destination)? {
+            return Ok(());
+        }
+        save_store.write(destination, &save_store.read(source)?)
+    }
+}
+
+impl SavedPaths {
+    /// Called once a path has finished loading. Everything loaded is already on disk, so it doesn't need saving again until it changes.
+    pub(super) fn loaded(
+        &mut self,
+        path: String,
+        from_save: bool,
+        deserialise_entity: &DeserialiseEntity,
+        this_run: Tick,
+    ) {
+        let next_index = deserialise_entity
+            .entities
+            .keys()
+            .max()
+            .map_or(0, |index| index + 1);
+
+        self.0.insert(
+            path,
+            SavedPath {
+                serialise_entity: SerialiseEntity {
+                    indices: deserialise_entity
+                        .entities
+                        .iter()
+                        .map(|(index, entity)| (*entity, *index))
+                        .collect(),
+                    next_index,
+                    ..default()
+                },
+                folder_names: deserialise_entity.folder_names.clone(),
+                // A backup or autosave is not what is in the save path, so the next save must write everything.
+                on_disk: from_save,
+                incremental: false,
+                ticks: deserialise_entity
+                    .components
+                    .keys()
+                    .map(|type_id| (*type_id, this_run))
+                    .collect(),
+                // Migrated components are still the old version on disk, so they count as not having a file yet.
+                files: deserialise_entity
+                    .components
+                    .iter()
+                    .map(|(type_id, indices)| {
+                        let migrated = deserialise_entity.migrated.get(type_id);
+                        let files = indices
+                            .iter()
+                            .filter(|index| {
+                                migrated.is_none_or(|migrated| !migrated.contains(*index))
+                            })
+                            .copied()
+                            .collect();
+                        (*type_id, files)
+                    })
+                    .collect(),
+                removed: vec![],
+                ..default()
+            },
+        );
+    }
+}
@@ -0,0 +1,152 @@
+use bevy::utils::HashSet;
+
+use super::{DeserialiseEntity, Referrer, SerialisedEntity};
+use crate::prelude::*;
+
+pub mod prelude {
+    pub use super::{DanglingReference, LoadIssuePolicy, LoadIssues};
+}
+
+/// What to do with entities that reference an entity that was never given any components.
+/// Those entities only exist because DeserialiseEntity::convert spawns an empty entity for any index it hasn't seen.
+#[derive(Resource, Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LoadIssuePolicy {
+    /// Leave everything as it is, including the empty entities.
+    #[default]
+    Keep,
+    /// Despawn every entity holding a dangling reference, along with the empty entities.
+    /// Resources holding one aren't loaded, so they stay as they were.
+    DropReferrer,
+    /// Despawn everything that was loaded from the path, and keep whatever was in the path before.
+    Fail,
+}
+
+/// A component or resource that references an entity that was never given any components.
+#[derive(Clone, Debug)]
+pub struct DanglingReference {
+    /// None if a resource holds the reference.
+    pub referrer: Option<Entity>,
+    pub referrer_index: Option<SerialisedEntity>,
+    /// The STRUCT_IDENT_LOWERCASE of the component or resource holding the reference.
+    pub component: &'static str,
+    /// The empty entity that was spawned in place of the missing one.
+    pub missing: Entity,
+    pub missing_index: SerialisedEntity,
+}
+
+/// Sent once a path has finished loading, if any dangling references were found.
+/// By the time this is read, the policy has already been applied.
+#[derive(Event, Clone, Debug)]
+pub struct LoadIssues {
+    pub path: String,
+    pub dangling: Vec<DanglingReference>,
+    pub policy: LoadIssuePolicy,
+}
+
+/// Checks loaded paths for dangling references, and applies the LoadIssuePolicy.
+#[derive(SystemParam)]
+pub struct LoadIssueReporter<'w> {
+    policy: Res<'w, LoadIssuePolicy>,
+    load_issues: EventWriter<'w, LoadIssues>,
+}
+
+impl LoadIssueReporter<'_> {
+    /// Finds dangling references in a path that has finished loading, and applies the LoadIssuePolicy.
+    /// Returns false if the load failed.
+    pub(super) fn check_integrity(
+        &mut self,
+        path: &str,
+        deserialise_entity: &mut DeserialiseEntity,
+        commands: &mut Commands,
+    ) -> bool {
+        let policy = *self.policy;
+
+        let loaded = deserialise_entity
+            .components
+            .values()
+            .flatten()
+            .copied()
+            .collect::<HashSet<_>>();
+
+        let dangling = deserialise_entity
+            .references
+            .iter()
+            .filter(|(_, _, referenced)| !loaded.contains(&referenced.0))
+            .filter_map(|(referrer, component, missing_index)| {
+                let (referrer, referrer_index) = match referrer {
+                    Referrer::Entity(index) => (
+                        Some(*deserialise_entity.entities.get(&index.0)?),
+                        Some(*index),
+                    ),
+                    Referrer::Resource => (None, None),
+                };
+                Some(DanglingReference {
+                    referrer,
+                    referrer_index,
+                    component,
+                    missing: *deserialise_entity.entities.get(&missing_index.0)?,
+                    missing_index: *missing_index,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        if dangling.is_empty() {
+            return true;
+        }
+
+        match policy {
+            LoadIssuePolicy::Keep => {
+                warn!(
+                    "Loaded {path} with {} dangling references. They have been kept.",
+                    dangling.len()
+                );
+            }
+            LoadIssuePolicy::DropReferrer => {
+                warn!(
+                    "Loaded {path} with {} dangling references. Their referrers have been dropped.",
+                    dangling.len()
+                );
+
+                let mut despawned = HashSet::new();
+                dangling.iter().for_each(|dangling| {
+                    dangling
+                        .referrer
+                        .into_iter()
+                        .chain([dangling.missing])
+                        .for_each(|entity| {
+                            if despawned.insert(entity) {
+                                commands.entity(entity).despawn();
+                            }
+                        });
+                });
+
+                let dropped = dangling
+                    .iter()
+                    .filter(|dangling| dangling.referrer.is_none())
+                    .map(|dangling| dangling.component)
+                    .collect::<HashSet<_>>();
+                deserialise_entity
+                    .resources
+                    .retain(|(resource, _)| !dropped.contains(resource));
+            }
+            LoadIssuePolicy::Fail => {
+                error!(
+                    "Failed to load {path}, as it has {} dangling references.",
+                    dangling.len()
+                );
+
+                deserialise_entity.entities.values().for_each(|entity| {
+                    commands.entity(*entity).despawn();
+                });
+            }
+        }
+
+        self.load_issues.send(LoadIssues {
+            path: path.to_string(),
+            dangling,
+            policy,
+        });
+
+        policy != LoadIssuePolicy::Fail
+    }
+}
@@ -0,0 +1,141 @@
+use bevy::utils::HashSet;
+
+use super::{DeserialiseEntity, Referrer, SaveConfig};
+use crate::prelude::*;
+
+pub mod prelude {
+    pub use super::{ComponentFilter, LoadOptions};
+}
+
+/// Changes how a path is loaded. The default loads it exactly as it was saved, replacing whatever is in the path.
+#[derive(Clone, Default, Debug)]
+pub struct LoadOptions {
+    /// Keep the entities already in the path, instead of despawning them first.
+    /// Loaded entities are always fresh ones, so nothing existing is overwritten.
+    /// Any whose SaveId is already taken in the path are given an unused one.
+    pub additive: bool,
+    /// Added to the translation of every loaded Transform, except those of children, which move with their parent.
+    /// Along with additive, this stamps a save into the world like a prefab.
+    pub offset: Vec3,
+    /// Which components and resources are loaded.
+    pub filter: ComponentFilter,
+}
+
+impl LoadOptions {
+    pub fn additive(mut self) -> Self {
+        self.additive = true;
+        self
+    }
+
+    pub fn offset(mut self, offset: Vec3) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Only load T, and anything else passed to only.
+    pub fn only<T: 'static>(mut self) -> Self {
+        match &mut self.filter {
+            ComponentFilter::Only(types) => {
+                types.insert(TypeId::of::<T>());
+            }
+            filter => *filter = ComponentFilter::Only(HashSet::from([TypeId::of::<T>()])),
+        }
+        self
+    }
+
+    /// Load everything except T, and anything else passed to except.
+    pub fn except<T: 'static>(mut self) -> Self {
+        match &mut self.filter {
+            ComponentFilter::Except(types) => {
+                types.insert(TypeId::of::<T>());
+            }
+            filter => *filter = ComponentFilter::Except(HashSet::from([TypeId::of::<T>()])),
+        }
+        self
+    }
+
+    /// Whether the path is loaded exactly as it was saved, so that what is in the world matches what is on disk.
+    pub(super) fn is_exact(&self) -> bool {
+        !self.additive && self.offset == Vec3::ZERO && self.filter.is_all()
+    }
+
+    /// Called once the path has finished loading.
+    pub(super) fn apply(&self, deserialise_entity: &DeserialiseEntity, commands: &mut Commands) {
+        // Children are moved along with their parent, so only entities without a loaded Parent are offset.
+        if self.offset != Vec3::ZERO {
+            let offset = self.offset;
+            let children = deserialise_entity.components.get(&TypeId::of::<Parent>());
+            deserialise_entity
+                .entities
+                .iter()
+                .filter(|(index, _)| children.is_none_or(|children| !children.contains(*index)))
+                .for_each(|(_, entity)| {
+                    commands
+                        .entity(*entity)
+                        .entry::<Transform>()
+                        .and_modify(move |mut transform| transform.translation += offset);
+                });
+        }
+
+        // SaveConfig is always loaded, so entities whose other components were all filtered out would be left with nothing else.
+        // They are kept if something that was loaded references them.
+        if let Some(save_configs) = deserialise_entity
+            .components
+            .get(&TypeId::of::<SaveConfig>())
+            .filter(|_| !self.filter.is_all())
+        {
+            let mut kept = deserialise_entity
+                .components
+                .iter()
+                .filter(|(type_id, _)| **type_id != TypeId::of::<SaveConfig>())
+                .flat_map(|(_, indices)| indices.iter().copied())
+                .collect::<HashSet<_>>();
+            kept.extend(
+                deserialise_entity
+                    .references
+                    .iter()
+                    .filter(|(referrer, _, _)| match referrer {
+                        Referrer::Entity(referrer) => kept.contains(&referrer.0),
+                        Referrer::Resource => true,
+                    })
+                    .map(|(_, _, referenced)| referenced.0)
+                    .collect::<Vec<_>>(),
+            );
+
+            save_configs
+                .difference(&kept)
+                .filter_map(|index| deserialise_entity.entities.get(index))
+                .for_each(|entity| {
+                    commands.entity(*entity).despawn();
+                });
+        }
+    }
+}
+
+/// Which components and resources are loaded. SaveConfig is always loaded, as it is what puts an entity in the path.
+#[derive(Clone, Default, Debug)]
+pub enum ComponentFilter {
+    #[default]
+    All,
+    Only(HashSet<TypeId>),
+    Except(HashSet<TypeId>),
+}
+
+impl ComponentFilter {
+    pub fn allows<T: 'static>(&self) -> bool {
+        let type_id = TypeId::of::<T>();
+        if type_id == TypeId::of::<SaveConfig>() {
+            return true;
+        }
+
+        match self {
+            Self::All => true,
+            Self::Only(types) => types.contains(&type_id),
+            Self::Except(types) => !types.contains(&type_id),
+        }
+    }
+
+    pub fn is_all(&self) -> bool {
+        matches!(self, Self::All)
+    }
+}
@@ -0,0 +1,287 @@
+use std::{
+    collections::{BTreeMap, HashMap as StdHashMap},
+    hash::{BuildHasher, Hash},
+};
+
+use serde::{de::DeserializeOwned, Deserializer, Serializer};
+
+use super::{DeserialiseEntity, SerialiseEntity, SerialisedEntity};
+use crate::prelude::*;
+
+pub mod prelude {
+    pub use super::MapSerialisedEntities;
+}
+
+/// Converts every entity inside a type to and from SerialisedEntity.
+/// The SaveAndLoad derive uses this for fields marked #[save(map)], such as Option<Entity> or Vec<(f32, Entity)>.
+/// Implement it for your own types if they hold entities and are used inside saved components.
+pub trait MapSerialisedEntities: Sized {
+    type Serialised: Serialize + DeserializeOwned;
+
+    fn serialise(&self, serialise_entity: &mut SerialiseEntity) -> Self::Serialised;
+
+    fn deserialise(
+        serialised: &Self::Serialised,
+        deserialise_entity: &mut DeserialiseEntity,
+        commands: &mut Commands,
+    ) -> Self;
+}
+
+impl MapSerialisedEntities for Entity {
+    type Serialised = SerialisedEntity;
+
+    fn serialise(&self, serialise_entity: &mut SerialiseEntity) -> Self::Serialised {
+        serialise_entity.convert(*self)
+    }
+
+    fn deserialise(
+        serialised: &Self::Serialised,
+        deserialise_entity: &mut DeserialiseEntity,
+        commands: &mut Commands,
+    ) -> Self {
+        deserialise_entity.convert(*serialised, commands)
+    }
+}
+
+/// Handles are saved as the path of their asset, and loaded again from it.
+/// Handles to assets without a path, such as ones made in code, can't be saved, so are loaded as the default handle.
+impl<A: Asset> MapSerialisedEntities for Handle<A> {
+    type Serialised = Option<String>;
+
+    fn serialise(&self, _: &mut SerialiseEntity) -> Self::Serialised {
+        self.path().map(ToString::to_string)
+    }
+
+    fn deserialise(
+        serialised: &Self::Serialised,
+        deserialise_entity: &mut DeserialiseEntity,
+        _: &mut Commands,
+    ) -> Self {
+        match (serialised, &deserialise_entity.asset_server) {
+            (Some(path), Some(asset_server)) => asset_server.load(path.clone()),
+            _ => Handle::default(),
+        }
+    }
+}
+
+//MARK: Leaves
+/// Types that can't hold entities are just cloned.
+/// They only need to implement MapSerialisedEntities so that containers holding them and entities work, such as (f32, Entity).
+macro_rules! map_serialised_entities_by_clone {
+    ($($type:ty),* $(,)?) => {
+        $(
+            impl MapSerialisedEntities for $type {
+                type Serialised = Self;
+
+                fn serialise(&self, _: &mut SerialiseEntity) -> Self::Serialised {
+                    self.clone()
+                }
+
+                fn deserialise(
+                    serialised: &Self::Serialised,
+                    _: &mut DeserialiseEntity,
+                    _: &mut Commands,
+                ) -> Self {
+                    serialised.clone()
+                }
+            }
+        )*
+    };
+}
+
+map_serialised_entities_by_clone!(
+    bool, char, u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64, String,
+    Vec2, Vec3, Vec4, IVec2, IVec3, UVec2, UVec3, Quat,
+);
+
+//MARK: Containers
+impl<T: MapSerialisedEntities> MapSerialisedEntities for Option<T> {
+    type Serialised = Option<T::Serialised>;
+
+    fn serialise(&self, serialise_entity: &mut SerialiseEntity) -> Self::Serialised {
+        self.as_ref().map(|value| value.serialise(serialise_entity))
+    }
+
+    fn deserialise(
+        serialised: &Self::Serialised,
+        deserialise_entity: &mut DeserialiseEntity,
+        commands: &mut Commands,
+    ) -> Self {
+        serialised
+            .as_ref()
+            .map(|value| T::deserialise(value, deserialise_entity, commands))
+    }
+}
+
+impl<T: MapSerialisedEntities> MapSerialisedEntities for Vec<T> {
+    type Serialised = Vec<T::Serialised>;
+
+    fn serialise(&self, serialise_entity: &mut SerialiseEntity) -> Self::Serialised {
+        self.iter()
+            .map(|value| value.serialise(serialise_entity))
+            .collect()
+    }
+
+    fn deserialise(
+        serialised: &Self::Serialised,
+        deserialise_entity: &mut DeserialiseEntity,
+        commands: &mut Commands,
+    ) -> Self {
+        serialised
+            .iter()
+            .map(|value| T::deserialise(value, deserialise_entity, commands))
+            .collect()
+    }
+}
+
+/// Serde only implements Serialize and Deserialize for arrays up to a length of 32, so this does it for any length.
+/// It is written as a normal json array.
+pub struct SerialisedArray<T, const N: usize>([T; N]);
+
+impl<T: Serialize, const N: usize> Serialize for SerialisedArray<T, N> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.as_slice().serialize(serializer)
+    }
+}
+
+impl<'de, T: Deserialize<'de>, const N: usize> Deserialize<'de> for SerialisedArray<T, N> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let values = Vec::<T>::deserialize(deserializer)?;
+        let length = values.len();
+
+        values.try_into().map(Self).map_err(|_| {
+            serde::de::Error::invalid_length(length, &format!("an array of length {N}").as_str())
+        })
+    }
+}
+
+impl<T: MapSerialisedEntities, const N: usize> MapSerialisedEntities for [T; N] {
+    type Serialised = SerialisedArray<T::Serialised, N>;
+
+    fn serialise(&self, serialise_entity: &mut SerialiseEntity) -> Self::Serialised {
+        SerialisedArray(
+            self.each_ref()
+                .map(|value| value.serialise(serialise_entity)),
+        )
+    }
+
+    fn deserialise(
+        serialised: &Self::Serialised,
+        deserialise_entity: &mut DeserialiseEntity,
+        commands: &mut Commands,
+    ) -> Self {
+        serialised
+            .0
+            .each_ref()
+            .map(|value| T::deserialise(value, deserialise_entity, commands))
+    }
+}
+
+/// Implements MapSerialisedEntities for a hash map type. Both keys and values are mapped.
+macro_rules! map_serialised_entities_for_hash_map {
+    ($($map:ident)::+) => {
+        impl<K, V, S> MapSerialisedEntities for $($map)::+<K, V, S>
+        where
+            K: MapSerialisedEntities + Eq + Hash,
+            K::Serialised: Eq + Hash,
+            V: MapSerialisedEntities,
+            S: BuildHasher + Default,
+        {
+            type Serialised = StdHashMap<K::Serialised, V::Serialised>;
+
+            fn serialise(&self, serialise_entity: &mut SerialiseEntity) -> Self::Serialised {
+                self.iter()
+                    .map(|(key, value)| {
+                        (
+                            key.serialise(serialise_entity),
+                            value.serialise(serialise_entity),
+                        )
+                    })
+                    .collect()
+            }
+
+            fn deserialise(
+                serialised: &Self::Serialised,
+                deserialise_entity: &mut DeserialiseEntity,
+                commands: &mut Commands,
+            ) -> Self {
+                serialised
+                    .iter()
+                    .map(|(key, value)| {
+                        (
+                            K::deserialise(key, deserialise_entity, commands),
+                            V::deserialise(value, deserialise_entity, commands),
+                        )
+                    })
+                    .collect()
+            }
+        }
+    };
+}
+
+map_serialised_entities_for_hash_map!(std::collections::HashMap);
+map_serialised_entities_for_hash_map!(bevy::utils::hashbrown::HashMap);
+
+impl<K, V> MapSerialisedEntities for BTreeMap<K, V>
+where
+    K: MapSerialisedEntities + Ord,
+    K::Serialised: Ord,
+    V: MapSerialisedEntities,
+{
+    type Serialised = BTreeMap<K::Serialised, V::Serialised>;
+
+    fn serialise(&self, serialise_entity: &mut SerialiseEntity) -> Self::Serialised {
+        self.iter()
+            .map(|(key, value)| {
+                (
+                    key.serialise(serialise_entity),
+                    value.serialise(serialise_entity),
+                )
+            })
+            .collect()
+    }
+
+    fn deserialise(
+        serialised: &Self::Serialised,
+        deserialise_entity: &mut DeserialiseEntity,
+        commands: &mut Commands,
+    ) -> Self {
+        serialised
+            .iter()
+            .map(|(key, value)| {
+                (
+                    K::deserialise(key, deserialise_entity, commands),
+                    V::deserialise(value, deserialise_entity, commands),
+                )
+            })
+            .collect()
+    }
+}
+
+/// Implements MapSerialisedEntities for a tuple of the given type parameters.
+macro_rules! map_serialised_entities_for_tuple {
+    ($($type:ident $index:tt),+) => {
+        impl<$($type: MapSerialisedEntities),+> MapSerialisedEntities for ($($type,)+) {
+            type Serialised = ($($type::Serialised,)+);
+
+            fn serialise(&self, serialise_entity: &mut SerialiseEntity) -> Self::Serialised {
+                ($(self.$index.serialise(serialise_entity),)+)
+            }
+
+            fn deserialise(
+                serialised: &Self::Serialised,
+                deserialise_entity: &mut DeserialiseEntity,
+                commands: &mut Commands,
+            ) -> Self {
+                ($($type::deserialise(&serialised.$index, deserialise_entity, commands),)+)
+            }
+        }
+    };
+}
+
+map_serialised_entities_for_tuple!(A 0);
+map_serialised_entities_for_tuple!(A 0, B 1);
+map_serialised_entities_for_tuple!(A 0, B 1, C 2);
+map_serialised_entities_for_tuple!(A 0, B 1, C 2, D 3);
+map_serialised_entities_for_tuple!(A 0, B 1, C 2, D 3, E 4);
+map_serialised_entities_for_tuple!(A 0, B 1, C 2, D 3, E 4, F 5);
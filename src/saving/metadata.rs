@@ -0,0 +1,162 @@
+use std::{collections::BTreeMap, time::SystemTime};
+
+use super::{save_id, storage::SaveStore, BUNDLE_FILE_NAME, SAVE_PATH};
+use crate::prelude::*;
+
+pub mod prelude {
+    pub use super::{Playtime, SaveListing, SaveMetadata, Saves, TypeMetadata};
+}
+
+/// Written into every save path, next to the entity folders. Loading ignores it.
+pub(super) const METADATA_FILE_NAME: &str = "meta.json";
+
+/// What a save was written by and what is in it, so a save-slot menu can show saves without loading them.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SaveMetadata {
+    pub written: SystemTime,
+    /// The path of the entities that were saved, as in their SaveConfig.
+    pub path: String,
+    /// The version of the game that wrote it.
+    pub version: String,
+    /// Each SaveAndLoad type in the save, keyed by its STRUCT_IDENT_LOWERCASE.
+    pub types: BTreeMap<String, TypeMetadata>,
+    pub entities: usize,
+    /// Whatever was passed to Save::path_described. Empty otherwise.
+    pub description: String,
+    /// The Playtime when it was saved.
+    #[serde(default)]
+    pub playtime: Duration,
+    /// The image passed to Save::path_with_screenshot, relative to the SaveStore's root.
+    /// It is only referred to, so the game has to keep it around for as long as the save.
+    #[serde(default)]
+    pub screenshot: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct TypeMetadata {
+    /// The VERSION it was saved with.
+    pub version: u32,
+    pub components: usize,
+}
+
+/// How long the game has been played for. Written into the metadata of every save.
+/// It only ever counts up, so set it from SaveMetadata::playtime after loading a save to carry on from there.
+#[derive(Resource, Default)]
+pub struct Playtime(pub Duration);
+
+/// Counts virtual time, so time spent paused isn't played.
+pub(super) fn count_playtime(time: Res<Time>, mut playtime: ResMut<Playtime>) {
+    playtime.0 += time.delta();
+}
+
+impl SaveMetadata {
+    pub(super) fn new(
+        path: String,
+        types: BTreeMap<String, TypeMetadata>,
+        entities: usize,
+        description: String,
+        playtime: Duration,
+        screenshot: Option<String>,
+    ) -> Self {
+        Self {
+            written: SystemTime::now(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            path,
+            types,
+            entities,
+            description,
+            playtime,
+            screenshot,
+        }
+    }
+
+    /// Writes the metadata into the folder.
+    /// Returns false if this failed.
+    pub(super) fn write(&self, save_store: &dyn SaveStorage, folder_path: &Path) -> bool {
+        let serialised = ok_or_error_and_return!(
+            serde_json::to_vec_pretty(self),
+            "Tried to save metadata. During serialisation got this error:",
+            false
+        );
+        ok_or_error_and_return!(
+            save_store.write(&folder_path.join(METADATA_FILE_NAME), &serialised),
+            "Tried to write metadata. Got this error:",
+            false
+        );
+
+        true
+    }
+}
+
+/// A save found by Saves::list.
+#[derive(Clone, Debug)]
+pub struct SaveListing {
+    /// Where the save is, relative to SAVE_PATH.
+    /// For autosaves and saves made with Save::path_to, this isn't the same as metadata.path.
+    pub path: String,
+    pub metadata: SaveMetadata,
+}
+
+/// Reads the metadata of saves without loading them.
+#[derive(SystemParam)]
+pub struct Saves<'w> {
+    save_store: Res<'w, SaveStore>,
+}
+
+impl Saves<'_> {
+    /// The metadata of the save at the path relative to SAVE_PATH.
+    /// None if there is no save there, it was written before metadata was, or the path isn't allowed.
+    pub fn metadata(&self, path: impl ToString) -> Option<SaveMetadata> {
+        let path = SavePath::new(path).ok()?;
+        self.read(&Path::new(SAVE_PATH).join(path))
+    }
+
+    /// Every save with metadata, including autosaves, newest first.
+    pub fn list(&self) -> Vec<SaveListing> {
+        let mut saves = vec![];
+        self.find(Path::new(""), &mut saves);
+        saves.sort_by_key(|save| std::cmp::Reverse(save.metadata.written));
+        saves
+    }
+
+    /// Save paths can be nested in folders, so this looks through every folder that isn't a save itself.
+    fn find(&self, path: &Path, saves: &mut Vec<SaveListing>) {
+        let folder = Path::new(SAVE_PATH).join(path);
+
+        if let Some(metadata) = self.read(&folder) {
+            saves.push(SaveListing {
+                path: path.to_string_lossy().into_owned(),
+                metadata,
+            });
+            return;
+        }
+
+        let Ok(entries) = self.save_store.read_dir(&folder) else {
+            return;
+        };
+
+        // A save written before metadata was. Its folders are entities, so there are no saves inside it.
+        if entries.iter().any(|entry| {
+            if entry.is_folder {
+                save_id::parse_folder_name(&entry.name).is_some()
+            } else {
+                entry.name == BUNDLE_FILE_NAME
+            }
+        }) {
+            return;
+        }
+
+        entries
+            .into_iter()
+            .filter(|entry| entry.is_folder)
+            .for_each(|entry| self.find(&path.join(entry.name), saves));
+    }
+
+    fn read(&self, folder: &Path) -> Option<SaveMetadata> {
+        let file = folder.join(METADATA_FILE_NAME);
+        if !self.save_store.exists(&file).unwrap_or(false) {
+            return None;
+        }
+        super::read_json(&**self.save_store, &file)
+    }
+}
@@ -0,0 +1,200 @@
+use serde_json::Value;
+
+use super::{SaveAndLoad, SerialisedEntity};
+use crate::prelude::*;
+
+pub mod prelude {
+    pub use super::{AppMigrationExtension, ComponentMigrated, Migration, Migrations};
+}
+
+/// Upgrades a serialised component from 1 version to the next.
+/// It is given the component exactly as it was written to disk, without the version.
+/// Returns an error if the component can't be upgraded, in which case it isn't loaded.
+pub type Migration = fn(&mut Value) -> Result<(), String>;
+
+/// Every registered migration, keyed by the SaveAndLoad type that they upgrade.
+/// The migration at index N upgrades from version N to version N + 1.
+#[derive(Resource, Default)]
+pub struct Migrations(HashMap<TypeId, Vec<Option<Migration>>>);
+
+impl Migrations {
+    /// Registers the migration that upgrades T from from_version to from_version + 1.
+    pub fn add<T: SaveAndLoad>(&mut self, from_version: u32, migration: Migration) {
+        let migrations = self.0.entry(TypeId::of::<T>()).or_default();
+        let from_version = from_version as usize;
+
+        if migrations.len() <= from_version {
+            migrations.resize(from_version + 1, None);
+        }
+
+        if migrations[from_version].replace(migration).is_some() {
+            warn!(
+                "Replaced the migration for {} from version {from_version}.",
+                T::STRUCT_IDENT_LOWERCASE
+            );
+        }
+    }
+
+    /// Upgrades the value from the version to T::VERSION, 1 version at a time.
+    pub fn migrate<T: SaveAndLoad>(
+        &self,
+        from_version: u32,
+        value: &mut Value,
+    ) -> Result<(), String> {
+        if from_version > T::VERSION {
+            return Err(format!(
+                "{} was saved with version {from_version}, which is newer than the current \
+                 version {}.",
+                T::STRUCT_IDENT_LOWERCASE,
+                T::VERSION
+            ));
+        }
+
+        let migrations = self.0.get(&TypeId::of::<T>());
+
+        (from_version..T::VERSION).try_for_each(|version| {
+            let Some(migration) = migrations
+                .and_then(|migrations| migrations.get(version as usize))
+                .copied()
+                .flatten()
+            else {
+                return Err(format!(
+                    "There is no migration for {} from version {version} to version {}.",
+                    T::STRUCT_IDENT_LOWERCASE,
+                    version + 1
+                ));
+            };
+
+            migration(value).map_err(|error| {
+                format!(
+                    "Tried to migrate {} from version {version} to version {}. {error}",
+                    T::STRUCT_IDENT_LOWERCASE,
+                    version + 1
+                )
+            })
+        })
+    }
+
+    /// Migrates the component if needed, and then deserialises it, without logging or reporting anything.
+    /// Also returns the version it was saved with.
+    pub fn deserialise<T: SaveAndLoad>(
+        &self,
+        value: &Value,
+    ) -> Result<(T::Serialised, u32), String> {
+        let (version, component) = split_version(value)?;
+
+        let component = if version == T::VERSION {
+            T::Serialised::deserialize(component)
+        } else {
+            let mut component = component.clone();
+            self.migrate::<T>(version, &mut component)?;
+            T::Serialised::deserialize(component)
+        };
+
+        component
+            .map(|component| (component, version))
+            .map_err(|error| format!("During deserialisation got this error: {error}"))
+    }
+
+    /// Like deserialise, but logs any error, and sends ComponentMigrated if it was migrated.
+    /// Returns None if it failed, or else the component and whether it was migrated.
+    pub fn read<T: SaveAndLoad>(
+        &self,
+        value: &Value,
+        file: &Path,
+        entity: SerialisedEntity,
+        component_migrated: &mut EventWriter<ComponentMigrated>,
+    ) -> Option<(T::Serialised, bool)> {
+        let (component, version) = match self.deserialise::<T>(value) {
+            Ok(deserialised) => deserialised,
+            Err(error) => {
+                error!("Tried to load {}. {error}", file.display());
+                return None;
+            }
+        };
+
+        let migrated = version != T::VERSION;
+        if migrated {
+            info!(
+                "Migrated {} from version {version} to version {}.",
+                file.display(),
+                T::VERSION
+            );
+            component_migrated.send(ComponentMigrated {
+                file: file.to_path_buf(),
+                entity,
+                type_id: TypeId::of::<T>(),
+                from_version: version,
+                to_version: T::VERSION,
+            });
+        }
+
+        Some((component, migrated))
+    }
+}
+
+/// Makes registering migrations easier, and doesn't care whether Migrations exists yet.
+pub trait AppMigrationExtension {
+    fn add_migration<T: SaveAndLoad>(
+        &mut self,
+        from_version: u32,
+        migration: Migration,
+    ) -> &mut Self;
+}
+
+impl AppMigrationExtension for App {
+    fn add_migration<T: SaveAndLoad>(
+        &mut self,
+        from_version: u32,
+        migration: Migration,
+    ) -> &mut Self {
+        self.init_resource::<Migrations>();
+        self.world_mut()
+            .resource_mut::<Migrations>()
+            .add::<T>(from_version, migration);
+        self
+    }
+}
+
+/// Sent whenever a component was saved with an older version, and was migrated while loading.
+/// Together these form a report of every file that was migrated.
+#[derive(Event, Debug)]
+pub struct ComponentMigrated {
+    /// The file the component was loaded from. For bundles this is the bundle itself.
+    pub file: PathBuf,
+    pub entity: SerialisedEntity,
+    pub type_id: TypeId,
+    pub from_version: u32,
+    pub to_version: u32,
+}
+
+/// The keys Versioned is written with. Fields can't be named this, so a component can never be mistaken for a versioned one.
+const VERSION_KEY: &str = "$version";
+const COMPONENT_KEY: &str = "$component";
+
+/// How every component is written to disk, so that the version can be read without knowing what the component looks like.
+#[derive(Serialize)]
+pub struct Versioned<'a, T> {
+    #[serde(rename = "$version")]
+    pub version: u32,
+    #[serde(rename = "$component")]
+    pub component: &'a T,
+}
+
+/// Splits what was written to disk into its version and component.
+/// Anything without VERSION_KEY was saved before versioning existed, so it is version 0.
+fn split_version(value: &Value) -> Result<(u32, &Value), String> {
+    let Some(version) = value.get(VERSION_KEY) else {
+        return Ok((0, value));
+    };
+
+    let version = version
+        .as_u64()
+        .and_then(|version| u32::try_from(version).ok())
+        .ok_or_else(|| format!("{VERSION_KEY} must be a whole number, but was {version}."))?;
+    let component = value
+        .get(COMPONENT_KEY)
+        .ok_or_else(|| format!("{VERSION_KEY} was there, but {COMPONENT_KEY} was missing."))?;
+
+    Ok((version, component))
+}
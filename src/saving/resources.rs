@@ -0,0 +1,123 @@
+use serde::de::DeserializeOwned;
+
+use super::{
+    archive::SaveTypes, autosave, incremental::SavedPaths, storage::SaveStore, DeserialiseEntity,
+    LoadComponents, Referrer, SaveComponents, SaveTransactions, SerialiseEntity, STAGING_PATH,
+};
+use crate::prelude::*;
+
+/// Resources are saved as a file in their save path, next to the entity folders.
+/// The file is named STRUCT_IDENT_LOWERCASE followed by this.
+pub(super) const FILE_SUFFIX: &str = ".resource.json";
+
+/// Like SaveAndLoad, but for a resource. Resources have no SaveConfig, so they belong to a single save path.
+/// Derive it with #[derive(SaveAndLoadResource)] and #[save(path = "path")].
+pub trait SaveAndLoadResource: Sized + Resource {
+    type Serialised: Serialize + DeserializeOwned;
+
+    const STRUCT_IDENT_LOWERCASE: &str;
+    /// The save path the resource is saved in, as passed to Save::path.
+    /// It is loaded from whichever path's save has it, so it isn't lost when that save is imported into another path.
+    const PATH: &str;
+
+    fn serialise(&self, serialise_entity: &mut SerialiseEntity) -> Self::Serialised;
+    fn deserialise(
+        serialised: &Self::Serialised,
+        deserialise_entity: &mut DeserialiseEntity,
+        commands: &mut Commands,
+    ) -> Self;
+
+    /// Puts the loaded resource into the world once its path has loaded. Replaces the current one, unless implemented otherwise.
+    fn insert(loaded: Self, commands: &mut Commands) {
+        commands.insert_resource(loaded);
+    }
+
+    fn save(
+        value: Option<Res<Self>>,
+        mut save_components: EventReader<SaveComponents>,
+        mut saved_paths: ResMut<SavedPaths>,
+        mut save_transactions: ResMut<SaveTransactions>,
+        save_store: Res<SaveStore>,
+    ) {
+        save_components.read().for_each(|save_components| {
+            if !SavePath::same(&save_components.path, Self::PATH) {
+                return;
+            }
+            let Some(value) = &value else {
+                return;
+            };
+
+            let destination = &save_components.destination;
+            let saved_path = saved_paths.0.entry(destination.clone()).or_default();
+            let serialised = value.serialise(&mut saved_path.serialise_entity);
+
+            let file_path = Path::new(STAGING_PATH)
+                .join(destination)
+                .join(format!("{}{FILE_SUFFIX}", Self::STRUCT_IDENT_LOWERCASE));
+
+            let serialised = match serde_json::to_vec_pretty(&serialised) {
+                Ok(serialised) => serialised,
+                Err(error) => {
+                    error!(
+                        "Tried to save a resource. During serialisation got this error: {error}"
+                    );
+                    save_transactions.fail(destination);
+                    return;
+                }
+            };
+            if let Err(error) = save_store.write(&file_path, &serialised) {
+                error!("Tried to write a resource. Got this error: {error}");
+                save_transactions.fail(destination);
+            }
+        });
+    }
+
+    fn load(mut load_components: EventReader<LoadComponents>, mut commands: Commands) {
+        load_components.read().for_each(|load_components| {
+            if !load_components.filter.allows::<Self>() {
+                return;
+            }
+
+            // Saves from before the resource was saved won't have it, so it is left as it is.
+            // Any path's save can, such as an archive of PATH imported somewhere else.
+            let Some(file) = load_components
+                .files
+                .resources
+                .get(Self::STRUCT_IDENT_LOWERCASE)
+            else {
+                return;
+            };
+
+            let serialised = ok_or_error_and_return!(
+                serde_json::from_slice::<Self::Serialised>(file),
+                "Tried to load a resource. During deserialisation got this error:"
+            );
+
+            let mut deserialise_entity = load_components.deserialise_entity();
+            deserialise_entity.referrer = Some((Referrer::Resource, Self::STRUCT_IDENT_LOWERCASE));
+            let loaded = Self::deserialise(&serialised, &mut deserialise_entity, &mut commands);
+            deserialise_entity.referrer = None;
+
+            // Held back until the path has loaded, so a resource referencing a missing entity is up to the LoadIssuePolicy.
+            deserialise_entity.resources.push((
+                Self::STRUCT_IDENT_LOWERCASE,
+                Box::new(move |commands: &mut Commands| Self::insert(loaded, commands)),
+            ));
+        });
+    }
+}
+
+pub fn setup_app_for_saving_and_loading_resource<T: SaveAndLoadResource>(
+    app: &mut App,
+) -> &mut App {
+    app.init_resource::<SaveTypes>();
+    app.world_mut()
+        .resource_mut::<SaveTypes>()
+        .add_resource::<T>();
+    app.init_resource::<Autosave>();
+    app.add_systems(
+        crate::Update_SaveAndLoad,
+        (T::save, T::load, autosave::mark_resource_changed::<T>),
+    );
+    app
+}
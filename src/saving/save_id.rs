@@ -0,0 +1,91 @@
+use bevy::utils::HashSet;
+
+use super::incremental::SavedPath;
+use crate::prelude::*;
+
+pub mod prelude {
+    pub use super::SaveId;
+}
+
+/// Identifies an entity within its save path. Given out the first time the entity is saved, and kept through loading.
+/// The entity's folder and any references to it use this, so they don't change between saves.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct SaveId(pub u32);
+
+/// The folder an entity is saved in. If it has a Name, that is put in front to make the save easier to read.
+fn folder_name(name: Option<&Name>, save_id: u32) -> String {
+    let Some(name) = name else {
+        return save_id.to_string();
+    };
+
+    let name = name
+        .as_str()
+        .chars()
+        .map(|character| {
+            if character.is_ascii_alphanumeric() || character == '_' || character == '-' {
+                character
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+
+    format!("{name}-{save_id}")
+}
+
+/// Gets the SaveId back out of a folder name, whether or not it has a name in front.
+pub(super) fn parse_folder_name(folder_name: &str) -> Option<u32> {
+    folder_name
+        .rsplit_once('-')
+        .map_or(folder_name, |(_, save_id)| save_id)
+        .parse()
+        .ok()
+}
+
+impl SavedPath {
+    /// Gives every entity in the save path a SaveId if it doesn't have one, and uses them as serialised entities.
+    pub(super) fn assign_save_ids<'a>(
+        &mut self,
+        entities: impl Iterator<Item = (Entity, Option<&'a SaveId>, Option<&'a Name>)>,
+        commands: &mut Commands,
+    ) {
+        // Whoever had a SaveId at the last save or load keeps it, so folders don't swap between entities sharing it.
+        let previous = std::mem::take(&mut self.serialise_entity.indices);
+        let mut entities = entities.collect::<Vec<_>>();
+        entities.sort_by_key(|(entity, save_id, _)| {
+            let kept = save_id.is_some_and(|save_id| previous.get(entity) == Some(&save_id.0));
+            (!kept, *entity)
+        });
+
+        let mut used = HashSet::new();
+        let mut unassigned = vec![];
+
+        entities.into_iter().for_each(|(entity, save_id, name)| {
+            // 2 entities can share a SaveId if 1 of them came from another save path.
+            let Some(save_id) = save_id.filter(|save_id| used.insert(save_id.0)) else {
+                unassigned.push((entity, name));
+                return;
+            };
+
+            self.serialise_entity.indices.insert(entity, save_id.0);
+            self.folder_names
+                .entry(save_id.0)
+                .or_insert_with(|| folder_name(name, save_id.0));
+        });
+
+        // New SaveIds must not collide with any in use.
+        if let Some(highest) = used.iter().max() {
+            self.serialise_entity.next_index = self.serialise_entity.next_index.max(highest + 1);
+        }
+
+        unassigned.into_iter().for_each(|(entity, name)| {
+            let save_id = self.serialise_entity.next_index;
+            self.serialise_entity.next_index += 1;
+
+            self.serialise_entity.indices.insert(entity, save_id);
+            self.folder_names
+                .insert(save_id, folder_name(name, save_id));
+            commands.entity(entity).insert(SaveId(save_id));
+        });
+    }
+}
@@ -0,0 +1,144 @@
+use std::{fmt, path::Component};
+
+use super::autosave::AUTOSAVE_PATH;
+use crate::prelude::*;
+
+pub mod prelude {
+    pub use super::{InvalidSavePath, SavePath, SavePathError};
+}
+
+/// Folders inside SAVE_PATH that saving uses itself, so no save path can start with them.
+const RESERVED: &[&str] = &[AUTOSAVE_PATH];
+
+/// A save path that is known to stay inside SAVE_PATH, so whatever is written or deleted for it can't touch anything else.
+/// Relative paths such as ./map are fine, but absolute paths, .. and empty paths are refused.
+/// Always normalised, so ./map, map and map/ are all map.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct SavePath(String);
+
+impl SavePath {
+    pub fn new(path: impl ToString) -> Result<Self, SavePathError> {
+        let path = Self::normalise(&path.to_string())?;
+
+        if RESERVED
+            .iter()
+            .any(|reserved| path.split('/').next() == Some(*reserved))
+        {
+            return Err(SavePathError::Reserved);
+        }
+
+        Ok(path)
+    }
+
+    /// Joins the names in the path with /, and drops any . in it.
+    fn normalise(path: &str) -> Result<Self, SavePathError> {
+        let mut names = vec![];
+
+        for component in Path::new(path).components() {
+            match component {
+                Component::Normal(name) => names.push(
+                    name.to_str()
+                        .expect("The path was made from a str, so each name is one too."),
+                ),
+                Component::CurDir => (),
+                Component::ParentDir => return Err(SavePathError::ParentFolder),
+                Component::RootDir | Component::Prefix(_) => return Err(SavePathError::Absolute),
+            }
+        }
+
+        if names.is_empty() {
+            return Err(SavePathError::Empty);
+        }
+
+        Ok(Self(names.join("/")))
+    }
+
+    /// A path inside 1 of the reserved folders, for saving itself to use.
+    pub(super) fn reserved(folder: &str, path: &SavePath, name: impl fmt::Display) -> Self {
+        debug_assert!(RESERVED.contains(&folder));
+        Self(format!("{folder}/{path}/{name}"))
+    }
+
+    /// Whether 2 paths are the same save path once normalised.
+    /// SaveConfig paths and SaveAndLoadResource::PATH aren't normalised, so they must be compared with this rather than ==.
+    pub fn same(path: &str, other: &str) -> bool {
+        fn names(path: &str) -> impl Iterator<Item = Component<'_>> {
+            Path::new(path)
+                .components()
+                .filter(|component| *component != Component::CurDir)
+        }
+
+        names(path).eq(names(other))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::ops::Deref for SavePath {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl AsRef<Path> for SavePath {
+    fn as_ref(&self) -> &Path {
+        Path::new(&self.0)
+    }
+}
+
+impl fmt::Display for SavePath {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(formatter)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SavePathError {
+    /// The path has no names in it, such as "" or ".".
+    Empty,
+    /// The path starts from the root, rather than from SAVE_PATH.
+    Absolute,
+    /// The path contains .., which could leave SAVE_PATH.
+    ParentFolder,
+    /// The path starts with a folder that saving uses itself, such as autosaves.
+    Reserved,
+}
+
+impl fmt::Display for SavePathError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(match self {
+            Self::Empty => "Save paths can't be empty.",
+            Self::Absolute => "Save paths must be relative.",
+            Self::ParentFolder => "Save paths can't contain ..",
+            Self::Reserved => "Save paths can't start with a folder that saving uses itself.",
+        })
+    }
+}
+
+impl std::error::Error for SavePathError {}
+
+/// Sent instead of saving or loading, when Save, Load or Autosave are given a path that isn't allowed.
+#[derive(Event, Clone, Debug)]
+pub struct InvalidSavePath {
+    pub path: String,
+    pub error: SavePathError,
+}
+
+impl InvalidSavePath {
+    /// Validates the path, sending this if it isn't allowed.
+    /// Sent through commands, so that Save and Load can be used in the same system.
+    pub(super) fn check(path: impl ToString, commands: &mut Commands) -> Option<SavePath> {
+        let path = path.to_string();
+        match SavePath::new(&path) {
+            Ok(save_path) => Some(save_path),
+            Err(error) => {
+                commands.send_event(InvalidSavePath { path, error });
+                None
+            }
+        }
+    }
+}
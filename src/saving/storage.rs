@@ -0,0 +1,427 @@
+use std::{
+    collections::BTreeMap,
+    io::{Error, ErrorKind, Result},
+    path::Component,
+    sync::{mpsc, Mutex},
+    time::SystemTime,
+};
+
+use notify::{EventKind, RecursiveMode, Watcher};
+
+use crate::prelude::*;
+
+pub mod prelude {
+    pub use super::{FileStorage, MemoryStorage, SaveStorage, SaveStore, StorageEntry};
+}
+
+/// Somewhere that saves can be written to and read from. Every path is relative to the storage's root.
+/// Saving and loading only ever go through this, so saves can be redirected anywhere, or kept entirely in memory.
+pub trait SaveStorage: Send + Sync {
+    fn read(&self, path: &Path) -> Result<Vec<u8>>;
+    /// Creates any missing parent folders, and replaces the file if it already exists.
+    fn write(&self, path: &Path, contents: &[u8]) -> Result<()>;
+    fn exists(&self, path: &Path) -> Result<bool>;
+    /// When the file was last written.
+    fn modified(&self, path: &Path) -> Result<SystemTime>;
+    /// Everything directly inside the folder.
+    fn read_dir(&self, path: &Path) -> Result<Vec<StorageEntry>>;
+    fn create_dir_all(&self, path: &Path) -> Result<()>;
+    fn remove_file(&self, path: &Path) -> Result<()>;
+    fn remove_dir_all(&self, path: &Path) -> Result<()>;
+    /// Moves a file or folder. The parent of to must exist, and to must not.
+    /// Saves rely on this being atomic, so a folder is never left half moved.
+    fn rename(&self, from: &Path, to: &Path) -> Result<()>;
+    /// Starts noticing files written in the folder and everything in it, by anything, until it is moved or removed.
+    /// Returns false if the storage can't, in which case HotReload compares when each file was modified instead.
+    fn watch(&self, _path: &Path) -> bool {
+        false
+    }
+    /// The files written in watched folders since this was last called.
+    fn written(&self) -> Vec<PathBuf> {
+        vec![]
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct StorageEntry {
+    pub name: String,
+    pub is_folder: bool,
+}
+
+/// The storage that saving and loading use. Defaults to ./assets on disk.
+/// Replace it before saving or loading anything, as saves in the previous storage won't be moved over.
+#[derive(Resource, Clone)]
+pub struct SaveStore(pub Arc<dyn SaveStorage>);
+
+impl std::ops::Deref for SaveStore {
+    type Target = dyn SaveStorage;
+
+    fn deref(&self) -> &Self::Target {
+        &*self.0
+    }
+}
+
+impl Default for SaveStore {
+    fn default() -> Self {
+        Self(Arc::new(FileStorage::new("./assets")))
+    }
+}
+
+//MARK: Files
+/// Stores saves in a folder on disk.
+pub struct FileStorage {
+    root: PathBuf,
+    /// Made the first time a folder is watched.
+    watcher: Mutex<Option<FileWatcher>>,
+}
+
+struct FileWatcher {
+    watcher: notify::RecommendedWatcher,
+    events: mpsc::Receiver<notify::Result<notify::Event>>,
+}
+
+impl FileStorage {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            watcher: Mutex::new(None),
+        }
+    }
+
+    fn watcher(&self) -> std::sync::MutexGuard<'_, Option<FileWatcher>> {
+        // The watcher is only ever replaced whole, so a panic can't leave it half changed.
+        self.watcher
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl SaveStorage for FileStorage {
+    fn read(&self, path: &Path) -> Result<Vec<u8>> {
+        fs::read(self.root.join(path))
+    }
+
+    fn write(&self, path: &Path, contents: &[u8]) -> Result<()> {
+        let path = self.root.join(path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, contents)
+    }
+
+    fn exists(&self, path: &Path) -> Result<bool> {
+        fs::exists(self.root.join(path))
+    }
+
+    fn modified(&self, path: &Path) -> Result<SystemTime> {
+        fs::metadata(self.root.join(path))?.modified()
+    }
+
+    fn read_dir(&self, path: &Path) -> Result<Vec<StorageEntry>> {
+        fs::read_dir(self.root.join(path))?
+            .map(|entry| {
+                let entry = entry?;
+                Ok(StorageEntry {
+                    name: entry.file_name().to_string_lossy().into_owned(),
+                    is_folder: entry.file_type()?.is_dir(),
+                })
+            })
+            .collect()
+    }
+
+    fn create_dir_all(&self, path: &Path) -> Result<()> {
+        fs::create_dir_all(self.root.join(path))
+    }
+
+    fn remove_file(&self, path: &Path) -> Result<()> {
+        fs::remove_file(self.root.join(path))
+    }
+
+    fn remove_dir_all(&self, path: &Path) -> Result<()> {
+        fs::remove_dir_all(self.root.join(path))
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> Result<()> {
+        fs::rename(self.root.join(from), self.root.join(to))
+    }
+
+    fn watch(&self, path: &Path) -> bool {
+        let mut watcher = self.watcher();
+
+        if watcher.is_none() {
+            let (sender, events) = mpsc::channel();
+            match notify::recommended_watcher(sender) {
+                Ok(created) => {
+                    *watcher = Some(FileWatcher {
+                        watcher: created,
+                        events,
+                    })
+                }
+                Err(error) => {
+                    warn!(
+                        "Tried to watch {} and got this error: {error}",
+                        path.display()
+                    );
+                    return false;
+                }
+            }
+        }
+        let Some(watcher) = watcher.as_mut() else {
+            return false;
+        };
+
+        // A watch follows the folder it was made on, which saving moves into the backups. So it is made again.
+        let path = self.root.join(path);
+        let _ = watcher.watcher.unwatch(&path);
+        if let Err(error) = watcher.watcher.watch(&path, RecursiveMode::Recursive) {
+            warn!(
+                "Tried to watch {} and got this error: {error}",
+                path.display()
+            );
+            return false;
+        }
+        true
+    }
+
+    fn written(&self) -> Vec<PathBuf> {
+        let watcher = self.watcher();
+        let Some(watcher) = watcher.as_ref() else {
+            return vec![];
+        };
+
+        watcher
+            .events
+            .try_iter()
+            .filter_map(|event| event.ok())
+            .filter(|event| matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)))
+            .flat_map(|event| event.paths)
+            .filter_map(|path| Some(path.strip_prefix(&self.root).ok()?.to_path_buf()))
+            .collect()
+    }
+}
+
+//MARK: Memory
+/// Stores saves in memory, so nothing touches the disk. Useful for tests.
+#[derive(Default)]
+pub struct MemoryStorage {
+    /// Every file and folder. Folders have no contents.
+    entries: Mutex<BTreeMap<PathBuf, Option<MemoryFile>>>,
+}
+
+struct MemoryFile {
+    contents: Vec<u8>,
+    modified: SystemTime,
+}
+
+impl MemoryStorage {
+    /// Paths are compared as keys, so ./a/b and a/b must become the same thing.
+    /// Anything that could escape the root is refused.
+    fn normalise(path: &Path) -> Result<PathBuf> {
+        path.components()
+            .filter(|component| *component != Component::CurDir)
+            .map(|component| match component {
+                Component::Normal(name) => Ok(name),
+                _ => Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("{} leaves the storage.", path.display()),
+                )),
+            })
+            .collect()
+    }
+
+    fn entries(&self) -> std::sync::MutexGuard<'_, BTreeMap<PathBuf, Option<MemoryFile>>> {
+        // A panic while holding the lock can't leave the map half changed, so it is fine to keep using it.
+        self.entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn not_found(path: &Path) -> Error {
+        Error::new(
+            ErrorKind::NotFound,
+            format!("{} does not exist.", path.display()),
+        )
+    }
+
+    fn create_parents(
+        entries: &mut BTreeMap<PathBuf, Option<MemoryFile>>,
+        path: &Path,
+    ) -> Result<()> {
+        for ancestor in path.ancestors().skip(1) {
+            if ancestor.as_os_str().is_empty() {
+                break;
+            }
+
+            match entries.get(ancestor) {
+                Some(Some(_)) => {
+                    return Err(Error::new(
+                        ErrorKind::AlreadyExists,
+                        format!("{} is a file, not a folder.", ancestor.display()),
+                    ))
+                }
+                Some(None) => (),
+                None => {
+                    entries.insert(ancestor.to_path_buf(), None);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl SaveStorage for MemoryStorage {
+    fn read(&self, path: &Path) -> Result<Vec<u8>> {
+        let path = Self::normalise(path)?;
+        match self.entries().get(&path) {
+            Some(Some(file)) => Ok(file.contents.clone()),
+            Some(None) => Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("{} is a folder.", path.display()),
+            )),
+            None => Err(Self::not_found(&path)),
+        }
+    }
+
+    fn write(&self, path: &Path, contents: &[u8]) -> Result<()> {
+        let path = Self::normalise(path)?;
+        let mut entries = self.entries();
+
+        if let Some(None) = entries.get(&path) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("{} is a folder.", path.display()),
+            ));
+        }
+
+        Self::create_parents(&mut entries, &path)?;
+        entries.insert(
+            path,
+            Some(MemoryFile {
+                contents: contents.to_vec(),
+                modified: SystemTime::now(),
+            }),
+        );
+        Ok(())
+    }
+
+    fn exists(&self, path: &Path) -> Result<bool> {
+        let path = Self::normalise(path)?;
+        Ok(path.as_os_str().is_empty() || self.entries().contains_key(&path))
+    }
+
+    fn modified(&self, path: &Path) -> Result<SystemTime> {
+        let path = Self::normalise(path)?;
+        match self.entries().get(&path) {
+            Some(Some(file)) => Ok(file.modified),
+            Some(None) => Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("{} is a folder.", path.display()),
+            )),
+            None => Err(Self::not_found(&path)),
+        }
+    }
+
+    fn read_dir(&self, path: &Path) -> Result<Vec<StorageEntry>> {
+        let path = Self::normalise(path)?;
+        let entries = self.entries();
+
+        if !path.as_os_str().is_empty() && !matches!(entries.get(&path), Some(None)) {
+            return Err(Self::not_found(&path));
+        }
+
+        Ok(entries
+            .iter()
+            .filter(|(entry, _)| entry.parent() == Some(path.as_path()))
+            .filter_map(|(entry, contents)| {
+                Some(StorageEntry {
+                    name: entry.file_name()?.to_string_lossy().into_owned(),
+                    is_folder: contents.is_none(),
+                })
+            })
+            .collect())
+    }
+
+    fn create_dir_all(&self, path: &Path) -> Result<()> {
+        let path = Self::normalise(path)?;
+        let mut entries = self.entries();
+
+        Self::create_parents(&mut entries, &path)?;
+        match entries.get(&path) {
+            Some(Some(_)) => Err(Error::new(
+                ErrorKind::AlreadyExists,
+                format!("{} is a file, not a folder.", path.display()),
+            )),
+            _ => {
+                entries.insert(path, None);
+                Ok(())
+            }
+        }
+    }
+
+    fn remove_file(&self, path: &Path) -> Result<()> {
+        let path = Self::normalise(path)?;
+        let mut entries = self.entries();
+
+        match entries.get(&path) {
+            Some(Some(_)) => {
+                entries.remove(&path);
+                Ok(())
+            }
+            Some(None) => Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("{} is a folder.", path.display()),
+            )),
+            None => Err(Self::not_found(&path)),
+        }
+    }
+
+    fn remove_dir_all(&self, path: &Path) -> Result<()> {
+        let path = Self::normalise(path)?;
+        let mut entries = self.entries();
+
+        if !matches!(entries.get(&path), Some(None)) {
+            return Err(Self::not_found(&path));
+        }
+
+        entries.retain(|entry, _| !entry.starts_with(&path));
+        Ok(())
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> Result<()> {
+        let from = Self::normalise(from)?;
+        let to = Self::normalise(to)?;
+        let mut entries = self.entries();
+
+        if !entries.contains_key(&from) {
+            return Err(Self::not_found(&from));
+        }
+        if entries.contains_key(&to) {
+            return Err(Error::new(
+                ErrorKind::AlreadyExists,
+                format!("{} already exists.", to.display()),
+            ));
+        }
+        if let Some(parent) = to.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            if !matches!(entries.get(parent), Some(None)) {
+                return Err(Self::not_found(parent));
+            }
+        }
+
+        let moved = entries
+            .keys()
+            .filter(|entry| entry.starts_with(&from))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        moved.into_iter().for_each(|entry| {
+            let contents = entries.remove(&entry).flatten();
+            let moved_to = match entry.strip_prefix(&from) {
+                Ok(relative) if !relative.as_os_str().is_empty() => to.join(relative),
+                _ => to.clone(),
+            };
+            entries.insert(moved_to, contents);
+        });
+
+        Ok(())
+    }
+}
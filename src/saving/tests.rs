@@ -0,0 +1,844 @@
+//! Saves, clears and loads a minimal app, with saves kept in memory, so nothing needs a window, GPU or disk.
+
+use std::collections::BTreeMap;
+
+use bevy::{
+    ecs::{
+        entity::{EntityMapper, MapEntities},
+        system::RunSystemOnce,
+    },
+    utils::HashSet,
+};
+
+use super::{
+    archive::SaveTypes, incremental::SavedPaths, setup_app_for_saving_and_loading,
+    setup_app_for_saving_and_loading_resource, LoadComponents, LoadPrepare, SaveBundles,
+    SaveComponents, SavePrepare, SaveTransactions,
+};
+use crate::prelude::*;
+
+/// The path every test saves to. Not ./map, as the game saves its own resources there.
+const PATH: &str = "./test";
+
+#[derive(Component, SaveAndLoad, Clone, PartialEq, Debug)]
+struct Health(u32);
+
+#[derive(Component, SaveAndLoad, Clone, PartialEq, Debug)]
+struct Target {
+    #[save(map)]
+    entity: Entity,
+    distance: f32,
+}
+
+#[derive(Component, SaveAndLoad, Clone, PartialEq, Debug)]
+enum Link {
+    Nothing,
+    One(#[save(map)] Entity),
+    Many {
+        #[save(map)]
+        entities: Vec<Entity>,
+        weight: f32,
+    },
+}
+
+/// Version 1 renamed amount to value.
+#[derive(Component, SaveAndLoad, Clone, PartialEq, Debug)]
+#[save(version = 1)]
+struct Armour {
+    value: u32,
+}
+
+#[derive(Resource, SaveAndLoadResource, PartialEq, Debug)]
+#[save(path = "./test")]
+struct Score(u32);
+
+#[derive(Resource, SaveAndLoadResource, PartialEq, Debug)]
+#[save(path = "./test")]
+struct Leader(#[save(map)] Entity);
+
+fn rename_amount(value: &mut serde_json::Value) -> Result<(), String> {
+    let amount = value
+        .as_object_mut()
+        .and_then(|object| object.remove("amount"))
+        .ok_or("There was no amount.")?;
+    value["value"] = amount;
+    Ok(())
+}
+
+impl MapEntities for Health {
+    fn map_entities<M: EntityMapper>(&mut self, _: &mut M) {}
+}
+
+impl MapEntities for Armour {
+    fn map_entities<M: EntityMapper>(&mut self, _: &mut M) {}
+}
+
+impl MapEntities for Target {
+    fn map_entities<M: EntityMapper>(&mut self, entity_mapper: &mut M) {
+        self.entity = entity_mapper.map_entity(self.entity);
+    }
+}
+
+impl MapEntities for Link {
+    fn map_entities<M: EntityMapper>(&mut self, entity_mapper: &mut M) {
+        match self {
+            Self::Nothing => (),
+            Self::One(entity) => *entity = entity_mapper.map_entity(*entity),
+            Self::Many { entities, .. } => entities
+                .iter_mut()
+                .for_each(|entity| *entity = entity_mapper.map_entity(*entity)),
+        }
+    }
+}
+
+/// Builds an app with only what saving and loading need, and the components the tests save.
+fn app() -> App {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, SavingPlugin))
+        .insert_resource(SaveStore(Arc::new(MemoryStorage::default())))
+        // Stand in for the schedule in main.rs, which runs Early before the rest of Update, and SaveAndLoad after it.
+        .add_systems(PreUpdate, |world: &mut World| {
+            world.run_schedule(crate::Update_Early);
+        })
+        .add_systems(
+            PostUpdate,
+            (|world: &mut World| world.run_schedule(crate::Update_SaveAndLoad))
+                .before(super::save_commit)
+                .before(super::finish_loads),
+        );
+    setup_app_for_saving_and_loading::<Health>(&mut app);
+    setup_app_for_saving_and_loading::<Target>(&mut app);
+    setup_app_for_saving_and_loading::<Link>(&mut app);
+    setup_app_for_saving_and_loading::<Armour>(&mut app);
+    setup_app_for_saving_and_loading_resource::<Score>(&mut app);
+    setup_app_for_saving_and_loading_resource::<Leader>(&mut app);
+    app.world_mut().resource_mut::<HotReload>().enabled = false;
+    app
+}
+
+/// Updates until an event that matches is sent, and returns it.
+/// Other paths could be saving or loading at the same time, so events for them are skipped.
+fn update_until<E: Event>(app: &mut App, matches: impl Fn(&E) -> bool) -> E {
+    for _ in 0..10 {
+        app.update();
+        let event = app
+            .world_mut()
+            .resource_mut::<Events<E>>()
+            .drain()
+            .find(&matches);
+        if let Some(event) = event {
+            return event;
+        }
+    }
+    panic!("{} was never sent.", std::any::type_name::<E>());
+}
+
+fn save(app: &mut App, path: &str) {
+    let path = path.to_string();
+    app.world_mut()
+        .run_system_once({
+            let path = path.clone();
+            move |mut save: Save| save.path(&path)
+        })
+        .unwrap();
+    let save_finished = update_until::<SaveFinished>(app, |save_finished| {
+        SavePath::same(&save_finished.destination, &path)
+    });
+    assert!(save_finished.succeeded);
+}
+
+/// Despawns everything in the path, and then loads it back.
+fn clear_and_load(app: &mut App, path: &str) {
+    let world = app.world_mut();
+    let saved = world
+        .query::<(Entity, &SaveConfig)>()
+        .iter(world)
+        .filter(|(_, save_config)| SavePath::same(&save_config.path, path))
+        .map(|(entity, _)| entity)
+        .collect::<Vec<_>>();
+    saved.into_iter().for_each(|entity| {
+        world.despawn(entity);
+    });
+
+    let path = path.to_string();
+    app.world_mut()
+        .run_system_once({
+            let path = path.clone();
+            move |mut load: Load| load.path(&path)
+        })
+        .unwrap();
+    let load_finished = update_until::<LoadFinished>(app, |load_finished| {
+        SavePath::same(&load_finished.path, &path)
+    });
+    assert!(load_finished.succeeded);
+}
+
+/// Swaps entities in the path for their SaveIds. SaveIds are only unique within a path, so anything outside it,
+/// including the empty entities loading spawns for references to them, becomes Entity::PLACEHOLDER.
+struct SaveIdMapper(HashMap<Entity, u32>);
+
+impl EntityMapper for SaveIdMapper {
+    fn map_entity(&mut self, entity: Entity) -> Entity {
+        self.0
+            .get(&entity)
+            .map_or(Entity::PLACEHOLDER, |save_id| Entity::from_raw(*save_id))
+    }
+}
+
+/// Every T in the path keyed by its entity's SaveId, with every entity it references swapped for their SaveId too.
+/// Entities are new after a load, but SaveIds are kept, so this is equal before saving and after loading
+/// only if both the values and what references what are.
+fn snapshot<T: Component + MapEntities + Clone>(app: &mut App, path: &str) -> BTreeMap<u32, T> {
+    let world = app.world_mut();
+    let mut mapper = SaveIdMapper(
+        world
+            .query::<(Entity, &SaveId, &SaveConfig)>()
+            .iter(world)
+            .filter(|(.., save_config)| SavePath::same(&save_config.path, path))
+            .map(|(entity, save_id, _)| (entity, save_id.0))
+            .collect(),
+    );
+
+    world
+        .query::<(&T, &SaveId, &SaveConfig)>()
+        .iter(world)
+        .filter(|(.., save_config)| SavePath::same(&save_config.path, path))
+        .map(|(value, save_id, _)| {
+            let mut value = value.clone();
+            value.map_entities(&mut mapper);
+            (save_id.0, value)
+        })
+        .collect()
+}
+
+/// Saves, clears and loads the path, and checks that every type came back the same.
+fn assert_round_trip(app: &mut App, path: &str) {
+    save(app, path);
+
+    let health = snapshot::<Health>(app, path);
+    let targets = snapshot::<Target>(app, path);
+    let links = snapshot::<Link>(app, path);
+
+    clear_and_load(app, path);
+
+    assert_eq!(snapshot::<Health>(app, path), health);
+    assert_eq!(snapshot::<Target>(app, path), targets);
+    assert_eq!(snapshot::<Link>(app, path), links);
+}
+
+fn config(format: SaveFormat) -> SaveConfig {
+    SaveConfig {
+        path: PATH.to_string(),
+        format,
+    }
+}
+
+/// A few entities that reference each other, including themselves and entities outside the path.
+fn spawn_entities(app: &mut App, format: SaveFormat) {
+    let world = app.world_mut();
+    let outside = world.spawn(Health(1)).id();
+    let a = world.spawn((config(format), Health(10))).id();
+    let b = world.spawn((config(format), Link::Nothing)).id();
+    let c = world
+        .spawn((
+            config(format),
+            Target {
+                entity: a,
+                distance: 2.5,
+            },
+            Link::Many {
+                entities: vec![a, b, outside],
+                weight: 0.5,
+            },
+        ))
+        .id();
+
+    world.entity_mut(a).insert((
+        Target {
+            entity: a,
+            distance: 0.,
+        },
+        Link::One(c),
+    ));
+    world.entity_mut(b).insert(Target {
+        entity: c,
+        distance: 1.,
+    });
+}
+
+#[test]
+fn round_trip_folders() {
+    let mut app = app();
+    spawn_entities(&mut app, SaveFormat::Folders);
+    assert_round_trip(&mut app, PATH);
+}
+
+#[test]
+fn round_trip_bundle() {
+    let mut app = app();
+    spawn_entities(&mut app, SaveFormat::Bundle);
+    assert_round_trip(&mut app, PATH);
+}
+
+#[test]
+fn round_trip_incremental() {
+    let mut app = app();
+    spawn_entities(&mut app, SaveFormat::Folders);
+    assert_round_trip(&mut app, PATH);
+
+    // The next save only writes what changed since the load.
+    let world = app.world_mut();
+    let mut healths = world.query::<&mut Health>();
+    healths.iter_mut(world).for_each(|mut health| health.0 += 1);
+    let despawned = world
+        .query_filtered::<Entity, With<Link>>()
+        .iter(world)
+        .next()
+        .unwrap();
+    world.despawn(despawned);
+
+    assert_round_trip(&mut app, PATH);
+
+    // Incremental saves are committed like full ones, so the previous save was backed up.
+    let save_store = app.world().resource::<SaveStore>().0.clone();
+    assert!(save_store
+        .exists(&Path::new(super::BACKUP_PATH).join(PATH).join("0"))
+        .unwrap());
+}
+
+#[test]
+fn round_trip_hierarchy() {
+    let mut app = app();
+    let world = app.world_mut();
+    let parent = world
+        .spawn((config(SaveFormat::Folders), Name::new("parent")))
+        .id();
+    let children = (0..3)
+        .map(|index| {
+            world
+                .spawn((
+                    config(SaveFormat::Folders),
+                    Name::new(format!("child {index}")),
+                    Transform::from_xyz(1., 2., index as f32),
+                ))
+                .id()
+        })
+        .collect::<Vec<_>>();
+    // Not the order they were spawned in, so their SaveIds don't give the order away.
+    world
+        .entity_mut(parent)
+        .add_children(&[children[2], children[0], children[1]]);
+
+    save(&mut app, PATH);
+    clear_and_load(&mut app, PATH);
+
+    let world = app.world_mut();
+    let mut names = world.query::<(Entity, &Name)>();
+    let mut named = |name: &str, world: &mut World| {
+        names
+            .iter(world)
+            .find(|(_, entity_name)| entity_name.as_str() == name)
+            .map(|(entity, _)| entity)
+            .unwrap()
+    };
+    let parent = named("parent", world);
+    let children = ["child 2", "child 0", "child 1"].map(|name| named(name, world));
+
+    assert!(children
+        .iter()
+        .all(|child| world.get::<Parent>(*child).map(Parent::get) == Some(parent)));
+    assert_eq!(
+        world
+            .get::<Children>(parent)
+            .map(|children| children.to_vec()),
+        Some(children.to_vec())
+    );
+    assert_eq!(
+        world
+            .get::<Transform>(children[0])
+            .map(|transform| transform.translation),
+        Some(Vec3::new(1., 2., 2.))
+    );
+}
+
+#[test]
+fn missing_parent_is_a_load_issue() {
+    let mut app = app();
+    *app.world_mut().resource_mut::<LoadIssuePolicy>() = LoadIssuePolicy::DropReferrer;
+    let world = app.world_mut();
+    let parent = world.spawn(Name::new("parent")).id();
+    let child = world
+        .spawn((config(SaveFormat::Folders), Name::new("child")))
+        .id();
+    world.entity_mut(parent).add_child(child);
+
+    save(&mut app, PATH);
+    clear_and_load(&mut app, PATH);
+    app.update();
+
+    let load_issues = app
+        .world_mut()
+        .resource_mut::<Events<LoadIssues>>()
+        .drain()
+        .collect::<Vec<_>>();
+    assert_eq!(load_issues.len(), 1);
+    assert_eq!(load_issues[0].dangling[0].component, "parent");
+
+    // The child was dropped along with the empty entity standing in for its parent.
+    let world = app.world_mut();
+    assert_eq!(world.query::<&Parent>().iter(world).count(), 0);
+    assert_eq!(
+        world
+            .query_filtered::<(), With<SaveConfig>>()
+            .iter(world)
+            .count(),
+        0
+    );
+}
+
+#[test]
+fn recovers_interrupted_save() {
+    let mut app = app();
+    spawn_entities(&mut app, SaveFormat::Folders);
+    save(&mut app, PATH);
+    let health = snapshot::<Health>(&mut app, PATH);
+
+    // As if the game stopped between the 2 renames of a commit, after the save became backup 0.
+    let save_store = app.world().resource::<SaveStore>().0.clone();
+    let backups_path = Path::new(super::BACKUP_PATH).join(PATH);
+    save_store.create_dir_all(&backups_path).unwrap();
+    save_store
+        .rename(
+            &Path::new(super::SAVE_PATH).join(PATH),
+            &backups_path.join("0"),
+        )
+        .unwrap();
+
+    clear_and_load(&mut app, PATH);
+    assert_eq!(snapshot::<Health>(&mut app, PATH), health);
+}
+
+#[test]
+fn migrates_old_versions() {
+    let mut app = app();
+    app.add_migration::<Armour>(0, rename_amount);
+    app.world_mut()
+        .spawn((config(SaveFormat::Folders), Armour { value: 5 }));
+    save(&mut app, PATH);
+
+    // Written before Armour had versions, so there is no version next to it.
+    let save_store = app.world().resource::<SaveStore>().0.clone();
+    let file = Path::new(super::SAVE_PATH)
+        .join(PATH)
+        .join("0")
+        .join("component.armour.json");
+    save_store.write(&file, br#"{"amount": 7}"#).unwrap();
+
+    clear_and_load(&mut app, PATH);
+    assert_eq!(
+        snapshot::<Armour>(&mut app, PATH)
+            .into_values()
+            .collect::<Vec<_>>(),
+        vec![Armour { value: 7 }]
+    );
+
+    // Nothing changed since the load, but the file is still the old version, so it is written again.
+    save(&mut app, PATH);
+    let written: serde_json::Value =
+        serde_json::from_slice(&save_store.read(&file).unwrap()).unwrap();
+    assert_eq!(written["$version"], 1);
+    assert_eq!(written["$component"]["value"], 7);
+
+    // A migration that fails skips the component, rather than loading it half upgraded.
+    save_store.write(&file, br#"{"armour": 7}"#).unwrap();
+    clear_and_load(&mut app, PATH);
+    assert!(snapshot::<Armour>(&mut app, PATH).is_empty());
+}
+
+#[test]
+fn loads_paths_together() {
+    let mut app = app();
+    let world = app.world_mut();
+    let other = |world: &mut World, health| {
+        world
+            .spawn((
+                SaveConfig {
+                    path: "./other".to_string(),
+                    format: SaveFormat::Folders,
+                },
+                Health(health),
+            ))
+            .id()
+    };
+    let a = other(world, 1);
+    let b = other(world, 2);
+    world.entity_mut(a).insert(Target {
+        entity: b,
+        distance: 3.,
+    });
+    spawn_entities(&mut app, SaveFormat::Folders);
+
+    save(&mut app, PATH);
+    save(&mut app, "./other");
+    let healths = [PATH, "./other"].map(|path| snapshot::<Health>(&mut app, path));
+    let targets = [PATH, "./other"].map(|path| snapshot::<Target>(&mut app, path));
+
+    // Both use the same SaveIds, so they would be mixed up if they shared a DeserialiseEntity.
+    let world = app.world_mut();
+    let saved = world
+        .query_filtered::<Entity, With<SaveConfig>>()
+        .iter(world)
+        .collect::<Vec<_>>();
+    saved.into_iter().for_each(|entity| {
+        world.despawn(entity);
+    });
+    world
+        .run_system_once(|mut load: Load| {
+            load.path(PATH);
+            load.path("./other");
+        })
+        .unwrap();
+    app.update();
+    app.update();
+
+    assert_eq!(
+        [PATH, "./other"].map(|path| snapshot::<Health>(&mut app, path)),
+        healths
+    );
+    assert_eq!(
+        [PATH, "./other"].map(|path| snapshot::<Target>(&mut app, path)),
+        targets
+    );
+}
+
+#[test]
+fn failed_load_keeps_previous_entities() {
+    let mut app = app();
+    spawn_entities(&mut app, SaveFormat::Folders);
+    save(&mut app, PATH);
+    let health = snapshot::<Health>(&mut app, PATH);
+    let targets = snapshot::<Target>(&mut app, PATH);
+
+    // Everything else references the entity with Health, so removing it leaves dangling references.
+    let save_store = app.world().resource::<SaveStore>().0.clone();
+    let save_id = *health.keys().next().unwrap();
+    save_store
+        .remove_dir_all(
+            &Path::new(super::SAVE_PATH)
+                .join(PATH)
+                .join(save_id.to_string()),
+        )
+        .unwrap();
+
+    *app.world_mut().resource_mut::<LoadIssuePolicy>() = LoadIssuePolicy::Fail;
+    app.world_mut()
+        .run_system_once(|mut load: Load| load.path(PATH))
+        .unwrap();
+    let load_finished = update_until::<LoadFinished>(&mut app, |load_finished| {
+        SavePath::same(&load_finished.path, PATH)
+    });
+    assert!(!load_finished.succeeded);
+    app.update();
+
+    assert_eq!(snapshot::<Health>(&mut app, PATH), health);
+    assert_eq!(snapshot::<Target>(&mut app, PATH), targets);
+    let world = app.world_mut();
+    assert_eq!(world.query::<&SaveConfig>().iter(world).count(), 3);
+}
+
+#[test]
+fn offset_moves_only_roots() {
+    let mut app = app();
+    let world = app.world_mut();
+    let parent = world
+        .spawn((config(SaveFormat::Folders), Transform::from_xyz(1., 0., 0.)))
+        .id();
+    let child = world
+        .spawn((config(SaveFormat::Folders), Transform::from_xyz(0., 1., 0.)))
+        .id();
+    world.entity_mut(parent).add_child(child);
+    save(&mut app, PATH);
+
+    app.world_mut()
+        .run_system_once(|mut load: Load| {
+            load.path_with(PATH, LoadOptions::default().offset(Vec3::Z));
+        })
+        .unwrap();
+    update_until::<LoadFinished>(&mut app, |load_finished| {
+        SavePath::same(&load_finished.path, PATH)
+    });
+    app.update();
+
+    // The child is relative to its parent, so it already moves with it.
+    let world = app.world_mut();
+    let mut translations = world
+        .query::<(&Transform, Has<Parent>)>()
+        .iter(world)
+        .map(|(transform, has_parent)| (has_parent, transform.translation))
+        .collect::<Vec<_>>();
+    translations.sort_by_key(|(has_parent, _)| *has_parent);
+    assert_eq!(
+        translations,
+        vec![
+            (false, Vec3::new(1., 0., 1.)),
+            (true, Vec3::new(0., 1., 0.))
+        ]
+    );
+}
+
+#[test]
+fn additive_loads_keep_save_ids_unique() {
+    let mut app = app();
+    spawn_entities(&mut app, SaveFormat::Folders);
+    save(&mut app, PATH);
+
+    for _ in 0..2 {
+        app.world_mut()
+            .run_system_once(|mut load: Load| {
+                load.path_with(PATH, LoadOptions::default().additive());
+            })
+            .unwrap();
+        update_until::<LoadFinished>(&mut app, |load_finished| {
+            SavePath::same(&load_finished.path, PATH)
+        });
+    }
+    app.update();
+
+    let save_ids = |app: &mut App| {
+        let world = app.world_mut();
+        world
+            .query::<(Entity, &SaveId, &SaveConfig)>()
+            .iter(world)
+            .filter(|(.., save_config)| SavePath::same(&save_config.path, PATH))
+            .map(|(entity, save_id, _)| (entity, save_id.0))
+            .collect::<HashMap<_, _>>()
+    };
+    let loaded = save_ids(&mut app);
+    assert_eq!(loaded.len(), 9);
+    assert_eq!(loaded.values().collect::<HashSet<_>>().len(), 9);
+
+    // Saving keeps them, rather than swapping any between entities.
+    save(&mut app, PATH);
+    assert_eq!(save_ids(&mut app), loaded);
+}
+
+#[test]
+fn save_paths_are_normalised() {
+    let mut app = app();
+    spawn_entities(&mut app, SaveFormat::Folders);
+
+    // The same path written differently is still the same save.
+    save(&mut app, "test/");
+    clear_and_load(&mut app, "test");
+    assert_eq!(snapshot::<Health>(&mut app, PATH).len(), 1);
+
+    app.world_mut()
+        .run_system_once(|mut save: Save| save.path("./autosaves/test"))
+        .unwrap();
+    app.update();
+    let errors = app
+        .world_mut()
+        .resource_mut::<Events<InvalidSavePath>>()
+        .drain()
+        .map(|invalid_save_path| invalid_save_path.error)
+        .collect::<Vec<_>>();
+    assert_eq!(errors, vec![SavePathError::Reserved]);
+}
+
+#[test]
+fn lists_saves_with_metadata() {
+    let mut app = app();
+    spawn_entities(&mut app, SaveFormat::Folders);
+    app.world_mut().resource_mut::<Playtime>().0 = Duration::from_secs(90);
+
+    app.world_mut()
+        .run_system_once(|mut save: Save| {
+            save.path_with_screenshot(PATH, "Described", "screenshots/test.png")
+        })
+        .unwrap();
+    let save_finished = update_until::<SaveFinished>(&mut app, |save_finished| {
+        SavePath::same(&save_finished.destination, PATH)
+    });
+    assert!(save_finished.succeeded);
+
+    // A save from before metadata. Its entity folder must not be mistaken for a nested save.
+    let save_store = app.world().resource::<SaveStore>().clone();
+    save_store
+        .write(
+            &Path::new(super::SAVE_PATH).join("old/0/meta.json"),
+            &save_store
+                .read(&Path::new(super::SAVE_PATH).join("test/meta.json"))
+                .unwrap(),
+        )
+        .unwrap();
+
+    let saves = app
+        .world_mut()
+        .run_system_once(|saves: Saves| saves.list())
+        .unwrap();
+    assert_eq!(saves.len(), 1);
+    assert_eq!(saves[0].path, "test");
+    assert_eq!(saves[0].metadata.description, "Described");
+    assert!(saves[0].metadata.playtime >= Duration::from_secs(90));
+    assert_eq!(
+        saves[0].metadata.screenshot.as_deref(),
+        Some("screenshots/test.png")
+    );
+}
+
+#[test]
+fn autosaves_only_changed_paths() {
+    let mut app = app();
+    spawn_entities(&mut app, SaveFormat::Folders);
+
+    let autosave_now = |app: &mut App| {
+        let mut autosave = app.world_mut().resource_mut::<Autosave>();
+        autosave.interval = EveryTime::new(Duration::from_secs(60), Duration::from_secs(61));
+    };
+    app.world_mut().resource_mut::<Autosave>().paths = vec![PATH.to_string(), "empty".to_string()];
+    // Changes are only noticed after the autosave system has run, so they are autosaved next time.
+    app.update();
+
+    autosave_now(&mut app);
+    let autosaved = update_until::<Autosaved>(&mut app, |_| true);
+    assert!(autosaved.succeeded);
+    assert!(SavePath::same(&autosaved.path, PATH));
+
+    // Nothing has changed since, so there is nothing to autosave.
+    autosave_now(&mut app);
+    for _ in 0..5 {
+        app.update();
+    }
+    assert!(app
+        .world_mut()
+        .resource_mut::<Events<Autosaved>>()
+        .drain()
+        .next()
+        .is_none());
+
+    let world = app.world_mut();
+    world
+        .query::<&mut Health>()
+        .iter_mut(world)
+        .for_each(|mut health| health.0 += 1);
+    app.update();
+    autosave_now(&mut app);
+    let autosaved = update_until::<Autosaved>(&mut app, |_| true);
+    assert_eq!(autosaved.slot, 1);
+
+    // Loading replaces every entity in the path, but that isn't a change.
+    // Not through clear_and_load, as autosaving has to see LoadFinished too.
+    save(&mut app, PATH);
+    app.world_mut()
+        .run_system_once(|mut load: Load| load.path(PATH))
+        .unwrap();
+    for _ in 0..5 {
+        app.update();
+    }
+    autosave_now(&mut app);
+    for _ in 0..5 {
+        app.update();
+    }
+    assert!(app
+        .world_mut()
+        .resource_mut::<Events<Autosaved>>()
+        .drain()
+        .next()
+        .is_none());
+
+    // Slots rotate themselves, so they aren't backed up.
+    let save_store = app.world().resource::<SaveStore>().clone();
+    assert!(!save_store
+        .exists(&Path::new(super::BACKUP_PATH).join("autosaves"))
+        .unwrap());
+}
+
+#[test]
+fn hot_reloads_bundles() {
+    let mut app = app();
+    app.world_mut().resource_mut::<HotReload>().enabled = true;
+    spawn_entities(&mut app, SaveFormat::Bundle);
+    save(&mut app, PATH);
+
+    // Not through clear_and_load, as HotReload has to see LoadFinished to start watching.
+    app.world_mut()
+        .run_system_once(|mut load: Load| load.path(PATH))
+        .unwrap();
+    for _ in 0..3 {
+        app.update();
+    }
+
+    // Edited as if by hand.
+    let save_store = app.world().resource::<SaveStore>().clone();
+    let file = Path::new(super::SAVE_PATH)
+        .join(PATH)
+        .join(super::BUNDLE_FILE_NAME);
+    let mut bundle =
+        serde_json::from_slice::<serde_json::Value>(&save_store.read(&file).unwrap()).unwrap();
+    bundle
+        .as_object_mut()
+        .unwrap()
+        .values_mut()
+        .filter_map(|components| components.pointer_mut("/health/$component"))
+        .for_each(|health| *health = 42.into());
+    std::thread::sleep(Duration::from_millis(5));
+    save_store
+        .write(&file, &serde_json::to_vec(&bundle).unwrap())
+        .unwrap();
+
+    app.world_mut().resource_mut::<Events<LoadFinish>>().clear();
+    app.world_mut().resource_mut::<HotReload>().interval =
+        EveryTime::new(Duration::from_secs(60), Duration::from_secs(61));
+    update_until::<LoadFinish>(&mut app, |_| true);
+    let healths = snapshot::<Health>(&mut app, PATH);
+    assert_eq!(healths.values().collect::<Vec<_>>(), vec![&Health(42)]);
+}
+
+#[test]
+fn imported_resources_load() {
+    let mut app = app();
+    spawn_entities(&mut app, SaveFormat::Folders);
+    app.insert_resource(Score(5));
+    save(&mut app, PATH);
+
+    app.world_mut()
+        .run_system_once(|mut save: Save| save.export(PATH, "shared"))
+        .unwrap();
+    app.world_mut().remove_resource::<Score>();
+
+    // The resource is only saved with ./test, but it is still loaded from wherever its save is imported.
+    app.world_mut()
+        .run_system_once(|mut load: Load| load.import("shared", "imported"))
+        .unwrap();
+    let load_finished = update_until::<LoadFinished>(&mut app, |load_finished| {
+        SavePath::same(&load_finished.path, "imported")
+    });
+    assert!(load_finished.succeeded);
+    assert_eq!(app.world().get_resource::<Score>(), Some(&Score(5)));
+}
+
+#[test]
+fn resources_with_dangling_references_are_load_issues() {
+    let mut app = app();
+    *app.world_mut().resource_mut::<LoadIssuePolicy>() = LoadIssuePolicy::DropReferrer;
+    let outside = app.world_mut().spawn_empty().id();
+    app.insert_resource(Leader(outside));
+    save(&mut app, PATH);
+
+    let before = app.world_mut().spawn_empty().id();
+    app.insert_resource(Leader(before));
+    clear_and_load(&mut app, PATH);
+    app.update();
+
+    let load_issues = app
+        .world_mut()
+        .resource_mut::<Events<LoadIssues>>()
+        .drain()
+        .collect::<Vec<_>>();
+    assert_eq!(load_issues.len(), 1);
+    assert_eq!(load_issues[0].dangling[0].component, "leader");
+    assert_eq!(load_issues[0].dangling[0].referrer, None);
+
+    // The resource wasn't loaded, so it still references what it did before.
+    assert_eq!(app.world().get_resource::<Leader>(), Some(&Leader(before)));
+}
@@ -49,6 +49,11 @@ impl Sun {
                 energy,
             },
             Radius(diameter / 2.),
+            // Falls through plants, to be absorbed by whatever is beneath.
+            CollisionLayers::new(
+                CollisionLayers::SUN,
+                CollisionLayers::ALL & !CollisionLayers::PLANT,
+            ),
             Transform::from_translation(Vec3::new(translation.x, translation.y, -1.)),
             Sprite {
                 image: asset_server.load("nodule.png"),
@@ -1,5 +1,7 @@
 use bevy::{ecs::schedule::ScheduleLabel, utils::HashMap};
 
+use crate::saving::{DeserialiseEntity, SaveAndLoadResource, SerialiseEntity};
+
 pub use crate::prelude::*;
 
 pub mod prelude {
@@ -55,6 +57,7 @@ impl Plugin for RunEveryPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<AllRunEverys>()
             .add_systems(Update, run_run_every_schedule);
+        crate::saving::setup_app_for_saving_and_loading_resource::<AllRunEverys>(app);
     }
 }
 
@@ -64,6 +67,47 @@ pub struct RunEvery(pub Duration);
 #[derive(Resource, Default)]
 pub struct AllRunEverys(pub HashMap<RunEvery, Duration>);
 
+// Manually implemented, as the schedules are added by the app, not the save. Only the time passed is loaded.
+impl SaveAndLoadResource for AllRunEverys {
+    /// (every, time passed)
+    type Serialised = Vec<(Duration, Duration)>;
+
+    const STRUCT_IDENT_LOWERCASE: &str = "allruneverys";
+    const PATH: &str = "./map";
+
+    fn serialise(&self, _: &mut SerialiseEntity) -> Self::Serialised {
+        self.0
+            .iter()
+            .map(|(run_every, time_passed)| (run_every.0, *time_passed))
+            .collect()
+    }
+
+    fn deserialise(
+        serialised: &Self::Serialised,
+        _: &mut DeserialiseEntity,
+        _: &mut Commands,
+    ) -> Self {
+        Self(
+            serialised
+                .iter()
+                .map(|(every, time_passed)| (RunEvery(*every), *time_passed))
+                .collect(),
+        )
+    }
+
+    /// Schedules that no longer exist are ignored, and new ones keep their offset.
+    fn insert(loaded: Self, commands: &mut Commands) {
+        commands.queue(move |world: &mut World| {
+            let mut all_run_everys = world.resource_mut::<AllRunEverys>();
+            loaded.0.into_iter().for_each(|(run_every, time_passed)| {
+                if let Some(current) = all_run_everys.0.get_mut(&run_every) {
+                    *current = time_passed;
+                }
+            });
+        });
+    }
+}
+
 pub trait AppTimeExtension {
     fn add_systems_that_run_every<M>(
         &mut self,
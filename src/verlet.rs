@@ -75,8 +75,13 @@ fn verlet_update(mut particles: Query<&mut Verlet>, time: Res<Time>) {
 fn solve_collisions(
     world: &mut World,
     system: &mut SystemState<(
-        Query<(Entity, &Radius, &Verlet)>,
-        Query<(&Radius, &Transform, Option<&Verlet>)>,
+        Query<(Entity, &Collider, &Verlet, Option<&CollisionLayers>)>,
+        Query<(
+            &Collider,
+            &Transform,
+            Option<&Verlet>,
+            Option<&CollisionLayers>,
+        )>,
         Res<ColliderGrid>,
     )>,
 
@@ -93,74 +98,72 @@ fn solve_collisions(
     for _ in 0..COLLISION_SUBSTEPS {
         let (particles, colliders, grid) = system.get(world);
 
-        particles.par_iter().for_each(|(entity, radius, particle)| {
-            // This is manually constructed, instead of using the ones already implemented on ColliderGrid.
-            // This is for extra optimisation, and ease of tinkering.
-
-            // We can keep this as the source of truth, and update it with every collision.
-            // This allows future collisions to be more accurate.
-            let mut translation = particle.translation;
-            let mut velocity = particle.velocity;
-            let radius = radius.0;
-
-            // Because translation can change, this is technically incorrect.
-            // We should instead work out grid index every time translation changes.
-            // That sounds slow, and complicated though, so we aren't going to do that.
-            let Some(grid_index) = grid.translation_to_index(translation) else {
-                return;
-            };
-
-            // At first we used any() so we could find 1 collision, solve it, and break early, but this caused some strange behaviour.
-            // TODO: Profile a for loop.
-            // TODO: Profile par_iter().
-            grid.cells[grid_index].0.iter().for_each(|other_entity| {
-                // Checking for collisions with yourself is pointless.
-                if entity == *other_entity {
-                    return;
-                }
-
-                // Get the collider information from the entity.
-                // other_translation is Verlet if the collider has it, and if not, then we use Transform.
-                let (other_radius, other_translation, collision_delta_multiplier) = {
-                    let Ok((other_radius, other_transform, other_translation)) =
-                        colliders.get(*other_entity)
-                    else {
+        particles
+            .par_iter()
+            .for_each(|(entity, collider, particle, layers)| {
+                // This is manually constructed, instead of using the ones already implemented on ColliderGrid.
+                // This is for extra optimisation, and ease of tinkering.
+
+                // We can keep this as the source of truth, and update it with every collision.
+                // This allows future collisions to be more accurate.
+                let mut translation = particle.translation;
+                let mut velocity = particle.velocity;
+                let layers = layers.copied().unwrap_or_default();
+
+                // Because translation can change, this is technically incorrect.
+                // We should instead work out the cell every time translation changes.
+                // That sounds slow, and complicated though, so we aren't going to do that.
+                let cell = grid.translation_to_cell(translation);
+
+                // At first we used any() so we could find 1 collision, solve it, and break early, but this caused some strange behaviour.
+                // TODO: Profile a for loop.
+                // TODO: Profile par_iter().
+                grid.cell(cell).iter().for_each(|other_entity| {
+                    // Checking for collisions with yourself is pointless.
+                    if entity == *other_entity {
                         return;
-                    };
-
-                    let (other_translation, collision_delta_multiplier) =
-                        if let Some(other_translation) = other_translation {
-                            (other_translation.translation, 0.5)
-                        } else {
-                            (other_transform.translation.xy(), 1.)
+                    }
+
+                    // Get the collider information from the entity.
+                    // other_translation is Verlet if the collider has it, and if not, then we use Transform.
+                    let (other_collider, other_translation, collision_delta_multiplier) = {
+                        let Ok((other_collider, other_transform, other_translation, other_layers)) =
+                            colliders.get(*other_entity)
+                        else {
+                            return;
                         };
 
-                    (
-                        other_radius.0,
-                        other_translation,
-                        collision_delta_multiplier,
-                    )
-                };
-
-                // This whole collision separation algorithm is taken and modified from https://www.youtube.com/watch?v=lS_qeBy3aQI at 4:09.
-                let radius_sum = radius + other_radius;
-
-                let collision_axis = translation - other_translation;
+                        // Layers that don't collide pass straight through each other.
+                        if !layers.interacts(&other_layers.copied().unwrap_or_default()) {
+                            return;
+                        }
+
+                        let (other_translation, collision_delta_multiplier) =
+                            if let Some(other_translation) = other_translation {
+                                (other_translation.translation, 0.5)
+                            } else {
+                                (other_transform.translation.xy(), 1.)
+                            };
+
+                        (
+                            other_collider,
+                            other_translation,
+                            collision_delta_multiplier,
+                        )
+                    };
 
-                // Collision can be checked using distance_squared, this saves a square root call.
-                let distance_squared = collision_axis.length_squared();
+                    // This whole collision separation algorithm is taken and modified from https://www.youtube.com/watch?v=lS_qeBy3aQI at 4:09.
+                    // The contact gives the collision axis and how far apart they need to move, whatever the shapes are.
+                    let Some(contact) =
+                        collider.contact(translation, other_collider, other_translation)
+                    else {
+                        return;
+                    };
 
-                if distance_squared <= radius_sum * radius_sum {
-                    let distance = distance_squared.sqrt();
-                    // Normalise the axis, so that it has no magnitude.
-                    // This works because distance is the magnitude of the collision axis.
-                    let collision_axis = collision_axis / distance;
-                    // How much to move along the collision axis to be not be intersecting each other.
-                    let distance_delta = radius_sum - distance;
                     // The change in translation needed to move 1 collider out of the other.
                     // When we move both, we just move each by half of this, in opposite directions.
                     let translation_delta =
-                        distance_delta * collision_axis * collision_delta_multiplier;
+                        contact.depth * contact.normal * collision_delta_multiplier;
                     // Friction.
                     let velocity_delta =
                         velocity.abs() * velocity * 0.01 * time_delta_seconds as f32;
@@ -168,13 +171,12 @@ fn solve_collisions(
                     // By keeping translation up to date with deferred changes, we can massively improve collision resolution.
                     translation += translation_delta;
                     velocity -= velocity_delta;
-                }
-            });
+                });
 
-            collision_resolutions
-                .borrow_local_mut()
-                .push((entity, translation, velocity));
-        });
+                collision_resolutions
+                    .borrow_local_mut()
+                    .push((entity, translation, velocity));
+            });
 
         collision_resolutions
             .iter_mut()
@@ -243,8 +245,11 @@ pub struct Extrapolate;
 
 #[system(Update::Early)]
 fn extrapolate(
-    mut particles: Query<(&Verlet, &Radius, &mut Transform), With<Extrapolate>>,
-    colliders: Query<(&Radius, &Transform), Without<Extrapolate>>,
+    mut particles: Query<
+        (&Verlet, &Radius, Option<&CollisionLayers>, &mut Transform),
+        With<Extrapolate>,
+    >,
+    colliders: ColliderQuery<Without<Extrapolate>>,
     time: Res<Time>,
     grid: Res<ColliderGrid>,
 ) {
@@ -253,31 +258,33 @@ fn extrapolate(
 
     particles
         .par_iter_mut()
-        .for_each(|(particle, radius, mut transform)| {
+        .for_each(|(particle, radius, layers, mut transform)| {
             let translation_delta = particle.velocity * time_delta_seconds
                 + particle.acceleration * halfed_after_squared_time_delta_seconds;
 
             let translation = transform.translation.xy() + translation_delta;
 
-            let collision = if let Some(grid_index) = grid.translation_to_index(translation) {
-                // Prevent jittering.
-                let radius = radius.0 * 1.1;
-                grid.cells[grid_index].0.iter().any(|other_entity| {
-                    let Ok((other_radius, other_transform)) = colliders.get(*other_entity) else {
-                        return false;
-                    };
+            // Prevent jittering.
+            let radius = radius.0 * 1.1;
+            let cell = grid.translation_to_cell(translation);
+            let layers = layers.copied().unwrap_or_default();
+            let collision = grid.cell(cell).iter().any(|other_entity| {
+                let Ok((other_collider, other_transform, other_layers)) =
+                    colliders.get(*other_entity)
+                else {
+                    return false;
+                };
 
-                    check_collision(
-                        radius,
-                        particle.translation,
-                        other_radius.0,
-                        other_transform.translation.xy(),
-                    )
-                })
-            } else {
-                // There cannot be collisions outside of the collision grid.
-                false
-            };
+                if !layers.interacts(&other_layers.copied().unwrap_or_default()) {
+                    return false;
+                }
+
+                other_collider.overlaps_circle(
+                    other_transform.translation.xy(),
+                    radius,
+                    particle.translation,
+                )
+            });
 
             // If there is not a collision then we can update the transform.
             if !collision {